@@ -0,0 +1,329 @@
+use crate::obj_db::ObjDb;
+use crate::parse;
+use crate::source::{FolderSource, Table, TableSource};
+use crate::validate::{Location, Rule, Severity};
+use crate::vfs::{mod_roots, Vfs};
+use crate::{Chara, ModuleDb};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+// Relative to the mod root, like the paths Vfs resolves
+pub fn objset_path(name: &str) -> String {
+    format!("rom/objset/{}.farc", name.to_lowercase())
+}
+
+pub(crate) fn read_chritm<S: TableSource + ?Sized>(source: &S) -> Option<parse::ChritmProp> {
+    let farc = parse::read_farc(&source.read(Table::ChritmProp)?)?;
+    Some((
+        parse::Costume::from_farc(&farc).unwrap_or_default(),
+        parse::CostumeItem::from_farc(&farc).unwrap_or_default(),
+    ))
+}
+
+// The tables of a mod merged over its include roots, earlier roots win like
+// they do in Vfs
+#[derive(Default)]
+struct ModTables {
+    costumes: BTreeMap<Chara, Vec<parse::Costume>>,
+    items: BTreeMap<Chara, BTreeMap<i32, parse::CostumeItem>>,
+    cstm_items: Vec<parse::CstmItem>,
+    obj_db: ObjDb,
+}
+
+impl ModTables {
+    fn read(roots: &[PathBuf]) -> Self {
+        let mut tables = Self::default();
+        for root in roots.iter().rev() {
+            let source = FolderSource::new(root.join("rom"));
+            if let Some((costumes, items)) = read_chritm(&source) {
+                for (chara, costumes) in costumes {
+                    tables
+                        .costumes
+                        .entry(chara)
+                        .or_default()
+                        .extend(costumes.data);
+                }
+                for (chara, items) in items {
+                    tables
+                        .items
+                        .entry(chara)
+                        .or_default()
+                        .extend(items.data.into_iter().map(|item| (item.no, item)));
+                }
+            }
+            if let Some(cstm_items) = source
+                .read(Table::GmCustomizeItem)
+                .and_then(|data| parse::CstmItem::from_bytes(&data))
+            {
+                tables.cstm_items.extend(cstm_items.data);
+            }
+            if let Some(obj_db) = ObjDb::from_source(&source) {
+                tables.obj_db.merge(obj_db);
+            }
+        }
+        tables
+    }
+}
+
+// Looks for the objset farcs used by a mod in the mod itself, then in vfs.
+// Objsets packed into another farc are found through their obj_db entry
+pub struct AssetCheck {
+    // The mod folder holding rom
+    pub root: PathBuf,
+    // Folders from the mods include list, each may contain a rom folder
+    pub roots: Vec<PathBuf>,
+    // The base game and the other mods
+    pub vfs: Option<Vfs>,
+    // The mod's obj_db merged over those of vfs
+    pub obj_db: ObjDb,
+    // Objsets of every chritm item, customize items only point at these by id
+    items: BTreeMap<Chara, BTreeMap<i32, Vec<String>>>,
+}
+
+impl AssetCheck {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        let root = root.into();
+        let roots = mod_roots(&root);
+        let tables = ModTables::read(&roots);
+        let items = tables
+            .items
+            .into_iter()
+            .map(|(chara, items)| {
+                let items = items
+                    .into_iter()
+                    .map(|(no, item)| (no, item.objset))
+                    .collect();
+                (chara, items)
+            })
+            .collect();
+
+        Self {
+            root,
+            roots,
+            vfs: None,
+            obj_db: tables.obj_db,
+            items,
+        }
+    }
+
+    pub fn with_vfs(mut self, vfs: Vfs) -> Self {
+        self.vfs = Some(vfs);
+        self.obj_db = ObjDb::from_folders(&self.rom_folders()).unwrap_or_default();
+        self
+    }
+
+    // Lowest priority first, so the mod's own entries win: the base game, the
+    // other mods, then the mod's roots
+    pub fn rom_folders(&self) -> Vec<PathBuf> {
+        let vfs = self.vfs.iter().flat_map(|vfs| {
+            vfs.base.iter().chain(
+                vfs.mods
+                    .iter()
+                    .rev()
+                    .flat_map(|vfs_mod| vfs_mod.roots.iter().rev()),
+            )
+        });
+        vfs.chain(self.roots.iter().rev())
+            .map(|root| root.join("rom"))
+            .collect()
+    }
+
+    fn find_farc(&self, name: &str) -> Option<PathBuf> {
+        let path = objset_path(name);
+        let mut local = self.roots.iter().flat_map(|root| {
+            [
+                root.join(&path),
+                root.join(format!("rom/objset/{name}.farc")),
+            ]
+        });
+        if let Some(local) = local.find(|path| path.is_file()) {
+            return Some(local);
+        }
+        Some(self.vfs.as_ref()?.resolve(&path)?.path)
+    }
+
+    // The farc named after the objset, or the archive its obj_db entry points at
+    pub fn find_objset(&self, name: &str) -> Option<PathBuf> {
+        if let Some(path) = self.find_farc(name) {
+            return Some(path);
+        }
+        self.find_farc(self.obj_db.archive(name)?)
+    }
+
+    fn missing(&self, location: Location, objsets: &[String]) -> Vec<(Location, String)> {
+        objsets
+            .iter()
+            .filter(|objset| self.find_objset(objset).is_none())
+            .map(|objset| {
+                (
+                    location.clone(),
+                    format!(
+                        "Objset {objset} not found, expected {}",
+                        objset_path(self.obj_db.archive(objset).unwrap_or(objset))
+                    ),
+                )
+            })
+            .collect()
+    }
+}
+
+impl Rule for AssetCheck {
+    fn id(&self) -> &'static str {
+        "missing-objset"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, module_db: &ModuleDb) -> Vec<(Location, String)> {
+        let mut findings = Vec::new();
+        for (id, module) in &module_db.modules {
+            for item in &module.cos.items {
+                let location = Location::CostumeItem {
+                    module: *id,
+                    item: item.id,
+                };
+                findings.extend(self.missing(location, &item.objset));
+            }
+        }
+        for (id, cstm_item) in &module_db.cstm_items {
+            let Some(objsets) = self
+                .items
+                .get(&cstm_item.chara)
+                .and_then(|items| items.get(&cstm_item.obj_id))
+            else {
+                continue;
+            };
+            findings.extend(self.missing(Location::CstmItem { id: *id }, objsets));
+        }
+        findings
+    }
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq)]
+pub struct UnusedObjset {
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq)]
+pub struct UnusedItem {
+    pub chara: Chara,
+    pub id: i32,
+    pub objset: Vec<String>,
+    // The objset farcs the mod ships only for this item
+    pub size: u64,
+}
+
+#[derive(Serialize, Clone, Default, PartialEq, Eq)]
+pub struct UnusedAssets {
+    pub objsets: Vec<UnusedObjset>,
+    pub items: Vec<UnusedItem>,
+}
+
+impl UnusedAssets {
+    pub fn is_empty(&self) -> bool {
+        self.objsets.is_empty() && self.items.is_empty()
+    }
+
+    pub fn size(&self) -> u64 {
+        let objsets = self.objsets.iter().map(|objset| objset.size).sum::<u64>();
+        let items = self.items.iter().map(|item| item.size).sum::<u64>();
+        objsets + items
+    }
+}
+
+// Farcs in rom/objset of every root by lowercase name, the first root wins
+fn objset_files(roots: &[PathBuf]) -> BTreeMap<String, (PathBuf, u64)> {
+    roots
+        .iter()
+        .rev()
+        .filter_map(|root| std::fs::read_dir(root.join("rom/objset")).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = path.file_name()?.to_str()?.to_lowercase();
+            let name = name.strip_suffix(".farc")?.to_string();
+            let size = entry.metadata().ok()?.len();
+            Some((name, (path, size)))
+        })
+        .collect()
+}
+
+// Objset farcs no chritm item references and chritm items no costume or
+// customize item uses, for the mod folder at root and its include roots. An
+// objset only used by an unused item is counted with the item
+pub fn unused_assets<P: AsRef<Path>>(root: P) -> UnusedAssets {
+    let roots = mod_roots(root);
+    let ModTables {
+        costumes,
+        items,
+        cstm_items,
+        obj_db,
+    } = ModTables::read(&roots);
+    let files = objset_files(&roots);
+
+    // Objsets in lowercase of every item, and of the items something uses,
+    // together with the farcs obj_db packs them into
+    let mut referenced = BTreeSet::new();
+    let mut kept = BTreeSet::new();
+    let mut unused_items = Vec::new();
+    for (chara, items) in items {
+        let used = costumes
+            .get(&chara)
+            .into_iter()
+            .flatten()
+            .flat_map(|cos| cos.item.iter().copied())
+            .chain(
+                cstm_items
+                    .iter()
+                    .filter(|item| item.chara == chara || item.chara == Chara::All)
+                    .map(|item| item.obj_id),
+            )
+            .collect::<BTreeSet<_>>();
+
+        for item in items.into_values() {
+            let objsets = item
+                .objset
+                .iter()
+                .flat_map(|objset| [Some(objset.as_str()), obj_db.archive(objset)])
+                .flatten()
+                .map(|objset| objset.to_lowercase())
+                .collect::<Vec<_>>();
+            referenced.extend(objsets.iter().cloned());
+            if used.contains(&item.no) {
+                kept.extend(objsets);
+            } else {
+                unused_items.push((chara.clone(), item, objsets));
+            }
+        }
+    }
+
+    // Shared objsets stay with the used items, and count once among unused ones
+    let mut counted = BTreeSet::new();
+    let mut unused = UnusedAssets::default();
+    for (chara, item, objsets) in unused_items {
+        let size = objsets
+            .into_iter()
+            .filter(|objset| !kept.contains(objset) && counted.insert(objset.clone()))
+            .filter_map(|objset| files.get(&objset))
+            .map(|(_, size)| size)
+            .sum();
+        unused.items.push(UnusedItem {
+            chara,
+            id: item.no,
+            objset: item.objset,
+            size,
+        });
+    }
+
+    unused.objsets = files
+        .into_iter()
+        .filter(|(name, _)| !referenced.contains(name))
+        .map(|(name, (path, size))| UnusedObjset { name, path, size })
+        .collect();
+    unused
+}
@@ -0,0 +1,132 @@
+use crate::vfs::{loader_priority, Vfs};
+use crate::ModuleDb;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+type ProgressFn<'a> = dyn Fn(&Path, usize, usize) + Sync + 'a;
+
+pub struct BatchLoad<'a> {
+    threads: usize,
+    progress: Option<Box<ProgressFn<'a>>>,
+}
+
+impl Default for BatchLoad<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> BatchLoad<'a> {
+    pub fn new() -> Self {
+        Self {
+            threads: std::thread::available_parallelism()
+                .map(|threads| threads.get())
+                .unwrap_or(1),
+            progress: None,
+        }
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    // Called with the finished root, the amount done and the total after every root
+    pub fn progress<F: Fn(&Path, usize, usize) + Sync + 'a>(mut self, progress: F) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    // Results are in the same order as roots, regardless of which finished first
+    pub fn load<P: AsRef<Path> + Sync>(&self, roots: &[P]) -> Vec<Option<ModuleDb>> {
+        let next = AtomicUsize::new(0);
+        let done = AtomicUsize::new(0);
+        let results = roots.iter().map(|_| Mutex::new(None)).collect::<Vec<_>>();
+
+        std::thread::scope(|scope| {
+            for _ in 0..self.threads.min(roots.len()) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(root) = roots.get(i) else {
+                        break;
+                    };
+
+                    let module_db = ModuleDb::from_folder(root);
+                    if let Ok(mut result) = results[i].lock() {
+                        *result = module_db;
+                    }
+
+                    let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                    if let Some(progress) = &self.progress {
+                        progress(root.as_ref(), done, roots.len());
+                    }
+                });
+            }
+        });
+
+        results
+            .into_iter()
+            .map(|result| result.into_inner().ok().flatten())
+            .collect()
+    }
+
+    // Roots are given highest priority first, their entries win over later ones
+    pub fn load_merged<P: AsRef<Path> + Sync>(&self, roots: &[P]) -> Option<ModuleDb> {
+        let mut merged: Option<ModuleDb> = None;
+        for module_db in self.load(roots).into_iter().rev().flatten() {
+            match &mut merged {
+                Some(merged) => merged.merge(module_db),
+                None => merged = Some(module_db),
+            }
+        }
+        merged
+    }
+
+    pub fn load_vfs(&self, vfs: &Vfs) -> Option<ModuleDb> {
+        let roots = vfs
+            .mods
+            .iter()
+            .flat_map(|vfs_mod| vfs_mod.roots.iter().map(|root| root.join("rom")))
+            .collect::<Vec<PathBuf>>();
+        self.load_merged(&roots)
+    }
+}
+
+impl ModuleDb {
+    // Every enabled mod in a DML mods folder, merged by DML's priority list when the
+    // game's config.toml is next to it and by name order otherwise
+    pub fn from_mods_folder<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let path = path.as_ref();
+        let vfs = Vfs::from_mods_folder(path, &loader_priority(path))?;
+        BatchLoad::new().load_vfs(&vfs)
+    }
+
+    // Accepts a rom folder, a single mod, a mods folder or a zip
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let path = path.as_ref();
+
+        #[cfg(feature = "zip")]
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+        {
+            let mut merged: Option<ModuleDb> = None;
+            for module_db in ModuleDb::from_zip(path)?.into_values().rev() {
+                match &mut merged {
+                    Some(merged) => merged.merge(module_db),
+                    None => merged = Some(module_db),
+                }
+            }
+            return merged;
+        }
+
+        if path.join("config.toml").exists() || path.join("rom").is_dir() {
+            Self::from_folder(path.join("rom"))
+        } else if path.join("lang2").is_dir() || path.join("mod_gm_module_tbl.farc").exists() {
+            Self::from_folder(path)
+        } else {
+            Self::from_mods_folder(path)
+        }
+    }
+}
@@ -0,0 +1,555 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use module_db::assets::{unused_assets, AssetCheck};
+use module_db::batch::BatchLoad;
+use module_db::obj_db::{ObjDb, ObjDbCheck};
+use module_db::spr_db::{SprDb, SprDbCheck};
+use module_db::tex_db::{TexDb, TexDbCheck};
+use module_db::validate::{Severity, Validator};
+use module_db::vfs::{loader_priority, Vfs};
+use module_db::{Chara, CustomizeItem, ItemPart, Language, Module, ModuleDb};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[command(
+    name = "module_db",
+    about = "Inspect the modules and customize items of DML mods"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the whole database
+    Dump {
+        path: PathBuf,
+        #[arg(long, value_enum, default_value = "json")]
+        format: DumpFormat,
+        /// Write names as separate name_* fields like older versions did
+        #[arg(long)]
+        compat: bool,
+    },
+    /// List modules and customize items
+    List {
+        path: PathBuf,
+        #[arg(long, value_parser = parse_chara)]
+        chara: Option<Chara>,
+        #[arg(long, value_parser = parse_part)]
+        part: Option<ItemPart>,
+        #[arg(long)]
+        modules: bool,
+        #[arg(long)]
+        items: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show a module with its costume items and objsets
+    Show {
+        path: PathBuf,
+        id: i32,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check the database for broken entries
+    Validate {
+        path: PathBuf,
+        /// Rule to skip, can be given more than once
+        #[arg(long)]
+        disable: Vec<String>,
+        /// Check objset farcs and the obj_db, tex_db and spr_db entries, path has to be a mod folder
+        #[arg(long)]
+        assets: bool,
+        /// Game folder the objsets may also come from, with its mods
+        #[arg(long, requires = "assets")]
+        game: Option<PathBuf>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Find files and ids provided by more than one mod
+    Conflicts {
+        mods: PathBuf,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Find objset farcs and chritm items a mod folder never uses
+    Unused {
+        path: PathBuf,
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DumpFormat {
+    Json,
+    Toml,
+}
+
+fn parse_chara(value: &str) -> Result<Chara, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_uppercase()))
+        .map_err(|_| format!("Invalid chara: {value}"))
+}
+
+fn parse_part(value: &str) -> Result<ItemPart, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_uppercase()))
+        .map_err(|_| format!("Invalid part: {value}"))
+}
+
+fn module_name(module: &Module) -> String {
+    module
+        .name
+        .get_or_fallback(Language::En)
+        .map(String::from)
+        .unwrap_or_default()
+}
+
+fn cstm_item_name(item: &CustomizeItem) -> String {
+    item.name
+        .get_or_fallback(Language::En)
+        .map(String::from)
+        .unwrap_or_default()
+}
+
+fn print_json<T: Serialize>(value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{json}"),
+        Err(err) => eprintln!("{err}"),
+    }
+}
+
+fn print_table(header: &[&str], rows: &[Vec<String>]) {
+    let mut widths = header.iter().map(|h| h.chars().count()).collect::<Vec<_>>();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: Vec<String>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    println!("{}", line(header.iter().map(|h| h.to_string()).collect()));
+    for row in rows {
+        println!("{}", line(row.clone()));
+    }
+}
+
+// toml can't represent nulls or integer keys, json already turned the keys into strings
+fn strip_nulls(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, strip_nulls(value)))
+                .collect(),
+        ),
+        serde_json::Value::Array(values) => {
+            serde_json::Value::Array(values.into_iter().map(strip_nulls).collect())
+        }
+        value => value,
+    }
+}
+
+fn dump<T: Serialize>(module_db: &T, format: DumpFormat) {
+    match format {
+        DumpFormat::Json => print_json(module_db),
+        DumpFormat::Toml => {
+            let value = serde_json::to_value(module_db).map(strip_nulls);
+            match value.map(|value| toml::to_string_pretty(&value)) {
+                Ok(Ok(toml)) => println!("{toml}"),
+                Ok(Err(err)) => eprintln!("{err}"),
+                Err(err) => eprintln!("{err}"),
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Listing {
+    modules: BTreeMap<i32, Module>,
+    cstm_items: BTreeMap<i32, CustomizeItem>,
+}
+
+fn list(
+    module_db: &ModuleDb,
+    chara: Option<Chara>,
+    part: Option<ItemPart>,
+    mut modules: bool,
+    mut items: bool,
+    json: bool,
+) {
+    if !modules && !items {
+        modules = true;
+        items = true;
+    }
+    // Modules have no part, so filtering by one only leaves items
+    if part.is_some() {
+        modules = false;
+    }
+
+    let listing = Listing {
+        modules: module_db
+            .modules
+            .iter()
+            .filter(|_| modules)
+            .filter(|(_, module)| chara.as_ref().is_none_or(|chara| chara == &module.chara))
+            .map(|(id, module)| (*id, module.clone()))
+            .collect(),
+        cstm_items: module_db
+            .cstm_items
+            .iter()
+            .filter(|_| items)
+            .filter(|(_, item)| chara.as_ref().is_none_or(|chara| chara == &item.chara))
+            .filter(|(_, item)| part.as_ref().is_none_or(|part| part == &item.part))
+            .map(|(id, item)| (*id, item.clone()))
+            .collect(),
+    };
+
+    if json {
+        print_json(&listing);
+        return;
+    }
+
+    if modules {
+        let rows = listing
+            .modules
+            .iter()
+            .map(|(id, module)| {
+                vec![
+                    id.to_string(),
+                    module.chara.to_string(),
+                    module.cos.id.to_string(),
+                    module_name(module),
+                ]
+            })
+            .collect::<Vec<_>>();
+        print_table(&["ID", "CHARA", "COS", "NAME"], &rows);
+    }
+    if modules && items {
+        println!();
+    }
+    if items {
+        let rows = listing
+            .cstm_items
+            .iter()
+            .map(|(id, item)| {
+                vec![
+                    id.to_string(),
+                    item.chara.to_string(),
+                    item.part.to_string(),
+                    item.bind_module
+                        .map(|id| id.to_string())
+                        .unwrap_or_default(),
+                    cstm_item_name(item),
+                ]
+            })
+            .collect::<Vec<_>>();
+        print_table(&["ID", "CHARA", "PART", "MODULE", "NAME"], &rows);
+    }
+}
+
+#[derive(Serialize)]
+struct ModuleDetails<'a> {
+    id: i32,
+    module: &'a Module,
+    cstm_items: BTreeMap<i32, &'a CustomizeItem>,
+}
+
+fn show(module_db: &ModuleDb, id: i32, json: bool) -> bool {
+    let Some(module) = module_db.modules.get(&id) else {
+        eprintln!("No module with id {id}");
+        return false;
+    };
+    let details = ModuleDetails {
+        id,
+        module,
+        cstm_items: module_db
+            .cstm_items
+            .iter()
+            .filter(|(_, item)| item.bind_module == Some(id))
+            .map(|(id, item)| (*id, item))
+            .collect(),
+    };
+
+    if json {
+        print_json(&details);
+        return true;
+    }
+
+    println!("Module {id}: {}", module_name(module));
+    println!("Chara: {}", module.chara.to_string());
+    println!("Costume: {}", module.cos.id);
+    for (lang, name) in module.name.iter() {
+        println!("  {}: {name}", lang.code().to_uppercase());
+    }
+
+    println!();
+    let rows = module
+        .cos
+        .items
+        .iter()
+        .map(|item| {
+            vec![
+                item.id.to_string(),
+                item.sub.to_string(),
+                item.objset.join(", "),
+            ]
+        })
+        .collect::<Vec<_>>();
+    print_table(&["ITEM", "SLOT", "OBJSET"], &rows);
+
+    if !details.cstm_items.is_empty() {
+        println!();
+        let rows = details
+            .cstm_items
+            .iter()
+            .map(|(id, item)| vec![id.to_string(), item.part.to_string(), cstm_item_name(item)])
+            .collect::<Vec<_>>();
+        print_table(&["CSTM ITEM", "PART", "NAME"], &rows);
+    }
+    true
+}
+
+fn validate(
+    module_db: &ModuleDb,
+    disable: &[String],
+    assets: Option<AssetCheck>,
+    json: bool,
+) -> bool {
+    let mut validator = Validator::new();
+    if let Some(assets) = assets {
+        // The databases come from the same layers the objsets do
+        let folders = assets.rom_folders();
+        if let Some(obj_db) = ObjDb::from_folders(&folders) {
+            validator = validator.with(ObjDbCheck::new(obj_db));
+        }
+        if let Some(tex_db) = TexDb::from_folders(&folders) {
+            validator = validator.with(TexDbCheck::new(tex_db));
+        }
+        if let Some(spr_db) = SprDb::from_folders(&folders) {
+            validator = validator.with(SprDbCheck::new(spr_db));
+        }
+        validator = validator.with(assets);
+    }
+    for rule in disable {
+        validator = validator.disable(rule);
+    }
+    let findings = validator.validate(module_db);
+
+    if json {
+        print_json(&findings);
+    } else if findings.is_empty() {
+        println!("No problems found");
+    } else {
+        let rows = findings
+            .iter()
+            .map(|finding| {
+                vec![
+                    finding.severity.to_string(),
+                    finding.rule.clone(),
+                    finding.location.to_string(),
+                    finding.message.clone(),
+                ]
+            })
+            .collect::<Vec<_>>();
+        print_table(&["SEVERITY", "RULE", "ENTRY", "PROBLEM"], &rows);
+    }
+    // Warnings alone don't fail the check
+    !findings
+        .iter()
+        .any(|finding| finding.severity == Severity::Error)
+}
+
+#[derive(Serialize)]
+struct Conflicts {
+    files: BTreeMap<String, Vec<String>>,
+    modules: BTreeMap<i32, Vec<String>>,
+    cstm_items: BTreeMap<i32, Vec<String>>,
+}
+
+fn conflicts(mods: &Path, json: bool) -> bool {
+    let Some(vfs) = Vfs::from_mods_folder(mods, &loader_priority(mods)) else {
+        eprintln!("Couldnt read mods folder {}", mods.display());
+        return false;
+    };
+
+    let roots = vfs
+        .mods
+        .iter()
+        .flat_map(|vfs_mod| {
+            vfs_mod
+                .roots
+                .iter()
+                .map(|root| (vfs_mod.name.clone(), root.join("rom")))
+        })
+        .collect::<Vec<_>>();
+    let paths = roots.iter().map(|(_, root)| root).collect::<Vec<_>>();
+    let loaded = BatchLoad::new().load(&paths);
+
+    let mut modules: BTreeMap<i32, Vec<String>> = BTreeMap::new();
+    let mut cstm_items: BTreeMap<i32, Vec<String>> = BTreeMap::new();
+    for ((name, _), module_db) in roots.iter().zip(loaded) {
+        let Some(module_db) = module_db else {
+            continue;
+        };
+        for id in module_db.modules.keys() {
+            modules.entry(*id).or_default().push(name.clone());
+        }
+        for id in module_db.cstm_items.keys() {
+            cstm_items.entry(*id).or_default().push(name.clone());
+        }
+    }
+    modules.retain(|_, mods| mods.len() > 1);
+    cstm_items.retain(|_, mods| mods.len() > 1);
+
+    let conflicts = Conflicts {
+        files: vfs.conflicts(),
+        modules,
+        cstm_items,
+    };
+    let found = !conflicts.files.is_empty()
+        || !conflicts.modules.is_empty()
+        || !conflicts.cstm_items.is_empty();
+
+    if json {
+        print_json(&conflicts);
+        return !found;
+    }
+    if !found {
+        println!("No conflicts found");
+        return true;
+    }
+
+    let mut rows = Vec::new();
+    for (file, mods) in &conflicts.files {
+        rows.push(vec![String::from("file"), file.clone(), mods.join(", ")]);
+    }
+    for (id, mods) in &conflicts.modules {
+        rows.push(vec![
+            String::from("module"),
+            id.to_string(),
+            mods.join(", "),
+        ]);
+    }
+    for (id, mods) in &conflicts.cstm_items {
+        rows.push(vec![
+            String::from("cstm_item"),
+            id.to_string(),
+            mods.join(", "),
+        ]);
+    }
+    // Mods are listed highest priority first, the first one wins
+    print_table(&["KIND", "ENTRY", "MODS"], &rows);
+    false
+}
+
+fn unused(path: &Path, json: bool) -> bool {
+    let unused = unused_assets(path);
+    if json {
+        print_json(&unused);
+        return unused.is_empty();
+    }
+    if unused.is_empty() {
+        println!("No unused assets found");
+        return true;
+    }
+
+    let mut rows = Vec::new();
+    for objset in &unused.objsets {
+        rows.push(vec![
+            String::from("objset"),
+            objset.name.clone(),
+            objset.size.to_string(),
+        ]);
+    }
+    for item in &unused.items {
+        rows.push(vec![
+            String::from("item"),
+            format!(
+                "{} item.{} ({})",
+                item.chara.to_string(),
+                item.id,
+                item.objset.join(", ")
+            ),
+            item.size.to_string(),
+        ]);
+    }
+    print_table(&["KIND", "ENTRY", "SIZE"], &rows);
+    println!("\nTotal: {} bytes", unused.size());
+    false
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let load_or_exit = |path: &Path| {
+        ModuleDb::from_path(path).unwrap_or_else(|| {
+            eprintln!("Couldnt load any modules from {}", path.display());
+            std::process::exit(1);
+        })
+    };
+
+    let success = match cli.command {
+        Command::Dump {
+            path,
+            format,
+            compat,
+        } => {
+            let module_db = load_or_exit(&path);
+            if compat {
+                dump(&module_db.to_compat(), format);
+            } else {
+                dump(&module_db, format);
+            }
+            true
+        }
+        Command::List {
+            path,
+            chara,
+            part,
+            modules,
+            items,
+            json,
+        } => {
+            list(&load_or_exit(&path), chara, part, modules, items, json);
+            true
+        }
+        Command::Show { path, id, json } => show(&load_or_exit(&path), id, json),
+        Command::Validate {
+            path,
+            disable,
+            assets,
+            game,
+            json,
+        } => {
+            let vfs = game.map(|game| match Vfs::from_game_folder(&game) {
+                Some(vfs) => vfs,
+                None => {
+                    eprintln!("Couldnt read the game folder {}", game.display());
+                    std::process::exit(1);
+                }
+            });
+            let assets = assets.then(|| match vfs {
+                Some(vfs) => AssetCheck::new(&path).with_vfs(vfs),
+                None => AssetCheck::new(&path),
+            });
+            validate(&load_or_exit(&path), &disable, assets, json)
+        }
+        Command::Conflicts { mods, json } => conflicts(&mods, json),
+        Command::Unused { path, json } => unused(&path, json),
+    };
+
+    if !success {
+        std::process::exit(1);
+    }
+}
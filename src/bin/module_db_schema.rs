@@ -0,0 +1,31 @@
+fn main() {
+    let mut json_schema = false;
+    let mut output = None;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--json-schema" => json_schema = true,
+            "--openapi" => json_schema = false,
+            _ => output = Some(arg),
+        }
+    }
+
+    let value = if json_schema {
+        module_db::openapi::json_schema()
+    } else {
+        serde_json::to_value(module_db::openapi::openapi()).unwrap_or_default()
+    };
+    let Ok(contents) = serde_json::to_string_pretty(&value) else {
+        eprintln!("Couldnt serialize schema");
+        std::process::exit(1);
+    };
+
+    match output {
+        Some(output) => {
+            if let Err(err) = std::fs::write(&output, contents) {
+                eprintln!("Couldnt write {output}: {err}");
+                std::process::exit(1);
+            }
+        }
+        None => println!("{contents}"),
+    }
+}
@@ -0,0 +1,30 @@
+use module_db::server::{router, ServerState};
+use std::path::PathBuf;
+
+#[tokio::main]
+async fn main() {
+    let mut args = std::env::args().skip(1);
+    let Some(mods) = args.next().map(PathBuf::from) else {
+        eprintln!("Usage: module_db_server <mods folder> [address]");
+        std::process::exit(1);
+    };
+    let address = args.next().unwrap_or(String::from("127.0.0.1:8080"));
+
+    let Some(state) = ServerState::from_mods_folder(&mods) else {
+        eprintln!("Couldnt load any mods from {}", mods.display());
+        std::process::exit(1);
+    };
+
+    let listener = match tokio::net::TcpListener::bind(&address).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("Couldnt bind {address}: {err}");
+            std::process::exit(1);
+        }
+    };
+    println!("Listening on {address}");
+    if let Err(err) = axum::serve(listener, router(state)).await {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
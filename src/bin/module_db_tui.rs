@@ -0,0 +1,290 @@
+use module_db::{Chara, Language, Localized, Module, ModuleDb};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+use std::collections::BTreeSet;
+
+struct App {
+    module_db: ModuleDb,
+    charas: Vec<Chara>,
+    // Index into charas, None shows every chara
+    chara: Option<usize>,
+    search: String,
+    searching: bool,
+    ids: Vec<i32>,
+    list: ListState,
+}
+
+fn display_name(name: &Localized) -> String {
+    name.get_or_fallback(Language::En)
+        .map(String::from)
+        .unwrap_or_default()
+}
+
+impl App {
+    fn new(module_db: ModuleDb) -> Self {
+        let charas = module_db
+            .modules
+            .values()
+            .map(|module| module.chara.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let mut app = Self {
+            module_db,
+            charas,
+            chara: None,
+            search: String::new(),
+            searching: false,
+            ids: Vec::new(),
+            list: ListState::default(),
+        };
+        app.refresh();
+        app
+    }
+
+    fn matches(&self, id: i32, module: &Module) -> bool {
+        if let Some(chara) = self.chara.and_then(|chara| self.charas.get(chara)) {
+            if chara != &module.chara {
+                return false;
+            }
+        }
+        if self.search.is_empty() {
+            return true;
+        }
+
+        let search = self.search.to_lowercase();
+        id.to_string().contains(&search)
+            || module
+                .name
+                .iter()
+                .any(|(_, name)| name.to_lowercase().contains(&search))
+    }
+
+    // Rebuilds the list after the filter changed, keeping the selection if possible
+    fn refresh(&mut self) {
+        let selected = self.selected();
+        let mut ids = self
+            .module_db
+            .modules
+            .iter()
+            .filter(|(id, module)| self.matches(**id, module))
+            .map(|(id, module)| (module.chara.clone(), *id))
+            .collect::<Vec<_>>();
+        ids.sort();
+        self.ids = ids.into_iter().map(|(_, id)| id).collect();
+
+        let index = selected
+            .and_then(|selected| self.ids.iter().position(|id| *id == selected))
+            .or(if self.ids.is_empty() { None } else { Some(0) });
+        self.list.select(index);
+    }
+
+    fn selected(&self) -> Option<i32> {
+        self.ids.get(self.list.selected()?).copied()
+    }
+
+    fn next_chara(&mut self, forward: bool) {
+        let count = self.charas.len();
+        self.chara = match (self.chara, forward) {
+            (None, true) if count > 0 => Some(0),
+            (None, false) if count > 0 => Some(count - 1),
+            (Some(chara), true) if chara + 1 < count => Some(chara + 1),
+            (Some(chara), false) if chara > 0 => Some(chara - 1),
+            _ => None,
+        };
+        self.refresh();
+    }
+
+    fn move_selection(&mut self, amount: isize) {
+        if self.ids.is_empty() {
+            return;
+        }
+        let current = self.list.selected().unwrap_or(0) as isize;
+        let index = (current + amount).clamp(0, self.ids.len() as isize - 1);
+        self.list.select(Some(index as usize));
+    }
+
+    // Returns false once the app should quit
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        if self.searching {
+            match code {
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Esc => {
+                    self.searching = false;
+                    self.search.clear();
+                    self.refresh();
+                }
+                KeyCode::Backspace => {
+                    self.search.pop();
+                    self.refresh();
+                }
+                KeyCode::Char(c) => {
+                    self.search.push(c);
+                    self.refresh();
+                }
+                KeyCode::Up => self.move_selection(-1),
+                KeyCode::Down => self.move_selection(1),
+                _ => {}
+            }
+            return true;
+        }
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Char('j') | KeyCode::Down => self.move_selection(1),
+            KeyCode::Char('k') | KeyCode::Up => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(20),
+            KeyCode::PageUp => self.move_selection(-20),
+            KeyCode::Home => self.move_selection(-(self.ids.len() as isize)),
+            KeyCode::End => self.move_selection(self.ids.len() as isize),
+            KeyCode::Tab => self.next_chara(true),
+            KeyCode::BackTab => self.next_chara(false),
+            _ => {}
+        }
+        true
+    }
+
+    fn details(&self) -> Vec<Line<'_>> {
+        let Some(id) = self.selected() else {
+            return vec![Line::from("No module selected")];
+        };
+        let Some(module) = self.module_db.modules.get(&id) else {
+            return Vec::new();
+        };
+
+        let mut lines = vec![
+            Line::from(format!("Module {id}: {}", display_name(&module.name))).bold(),
+            Line::from(format!("Chara: {}", module.chara.to_string())),
+            Line::from(format!("Costume: {}", module.cos.id)),
+            Line::default(),
+            Line::from("Names").bold(),
+        ];
+        for lang in Language::ALL {
+            let name = module.name.get(lang).unwrap_or("-");
+            lines.push(Line::from(format!(
+                "  {:<8}{name}",
+                lang.code().to_uppercase()
+            )));
+        }
+
+        lines.push(Line::default());
+        lines.push(Line::from("Costume items").bold());
+        if module.cos.items.is_empty() {
+            lines.push(Line::from("  None"));
+        }
+        for item in &module.cos.items {
+            lines.push(Line::from(format!(
+                "  {:<6}{:<20}{}",
+                item.id,
+                item.sub.to_string(),
+                item.objset.join(", ")
+            )));
+        }
+
+        let cstm_items = self
+            .module_db
+            .cstm_items
+            .iter()
+            .filter(|(_, item)| item.bind_module == Some(id))
+            .collect::<Vec<_>>();
+        lines.push(Line::default());
+        lines.push(Line::from("Customize items").bold());
+        if cstm_items.is_empty() {
+            lines.push(Line::from("  None"));
+        }
+        for (id, item) in cstm_items {
+            let name = display_name(&item.name);
+            lines.push(Line::from(format!(
+                "  {:<6}{:<14}{}",
+                id,
+                item.part.to_string(),
+                name
+            )));
+        }
+
+        lines
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(main);
+
+        let chara = match self.chara.and_then(|chara| self.charas.get(chara)) {
+            Some(chara) => chara.to_string(),
+            None => String::from("All"),
+        };
+        let items = self
+            .ids
+            .iter()
+            .filter_map(|id| {
+                let module = self.module_db.modules.get(id)?;
+                Some(format!(
+                    "{id:>5} {:<7}{}",
+                    module.chara.to_string(),
+                    display_name(&module.name)
+                ))
+            })
+            .collect::<Vec<_>>();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!(" Modules ({chara}, {}) ", self.ids.len())))
+            .highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(list, left, &mut self.list);
+
+        let details = Paragraph::new(self.details())
+            .block(Block::bordered().title(" Details "))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(details, right);
+
+        let status_line = if self.searching {
+            format!("/{}", self.search)
+        } else if !self.search.is_empty() {
+            format!("Filter: {}  (/ search, Tab chara, q quit)", self.search)
+        } else {
+            String::from("/ search, Tab chara, j/k move, q quit")
+        };
+        frame.render_widget(Paragraph::new(status_line), status);
+    }
+}
+
+fn main() {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("Usage: module_db_tui <path>");
+        std::process::exit(1);
+    };
+    let Some(module_db) = ModuleDb::from_path(&path) else {
+        eprintln!("Couldnt load any modules from {path}");
+        std::process::exit(1);
+    };
+
+    let mut app = App::new(module_db);
+    let mut terminal = ratatui::init();
+    let result = loop {
+        if let Err(err) = terminal.draw(|frame| app.draw(frame)) {
+            break Err(err);
+        }
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                if !app.handle_key(key.code) {
+                    break Ok(());
+                }
+            }
+            Ok(_) => {}
+            Err(err) => break Err(err),
+        }
+    };
+    ratatui::restore();
+
+    if let Err(err) = result {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,48 @@
+// Little endian readers for the classic database files, offsets are from the start of the file
+
+pub fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes(bytes.try_into().ok()?))
+}
+
+pub fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+#[cfg(feature = "thumbnail")]
+pub fn read_f32(data: &[u8], offset: usize) -> Option<f32> {
+    read_u32(data, offset).map(f32::from_bits)
+}
+
+pub fn read_offset(data: &[u8], offset: usize) -> Option<usize> {
+    read_u32(data, offset).map(|offset| offset as usize)
+}
+
+// Null terminated string at the offset stored at offset
+pub fn read_string(data: &[u8], offset: usize) -> Option<String> {
+    let start = read_offset(data, offset)?;
+    let bytes = data.get(start..)?;
+    let end = bytes.iter().position(|byte| *byte == 0)?;
+    Some(String::from_utf8_lossy(&bytes[..end]).to_string())
+}
+
+// Writers for building test buffers
+#[cfg(test)]
+pub mod write {
+    pub fn put_u16(data: &mut [u8], offset: usize, value: u16) {
+        data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn put_u32(data: &mut [u8], offset: usize, value: u32) {
+        data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    // Appends the string and stores its offset at offset
+    pub fn put_string(data: &mut Vec<u8>, offset: usize, value: &str) {
+        let start = data.len() as u32;
+        data.extend_from_slice(value.as_bytes());
+        data.push(0);
+        put_u32(data, offset, start);
+    }
+}
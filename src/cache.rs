@@ -0,0 +1,284 @@
+use crate::batch::BatchLoad;
+use crate::source::input_files;
+use crate::ModuleDb;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// Bump whenever the layout of ModuleDb or the cache itself changes
+const CACHE_VERSION: u32 = 6;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+struct FileStamp {
+    size: u64,
+    mtime: SystemTime,
+    hash: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct CacheEntry {
+    // Keyed by the path relative to the rom folder
+    inputs: BTreeMap<String, Option<FileStamp>>,
+    module_db: Option<ModuleDb>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Cache {
+    version: u32,
+    entries: BTreeMap<PathBuf, CacheEntry>,
+}
+
+// FNV-1a, std's hasher isn't guaranteed to be stable between releases
+fn hash(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn stamp(path: &Path, cached: Option<&FileStamp>) -> Option<FileStamp> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let size = metadata.len();
+    let mtime = metadata.modified().ok()?;
+
+    // Only hash when the cheap checks disagree
+    if let Some(cached) = cached {
+        if cached.size == size && cached.mtime == mtime {
+            return Some(cached.clone());
+        }
+    }
+
+    Some(FileStamp {
+        size,
+        mtime,
+        hash: hash(&std::fs::read(path).ok()?),
+    })
+}
+
+fn stamps(root: &Path, cached: Option<&CacheEntry>) -> BTreeMap<String, Option<FileStamp>> {
+    input_files()
+        .into_iter()
+        .map(|file| {
+            let cached = cached.and_then(|entry| entry.inputs.get(&file)?.as_ref());
+            let stamp = stamp(&root.join(&file), cached);
+            (file, stamp)
+        })
+        .collect()
+}
+
+impl Cache {
+    pub fn new() -> Self {
+        Self {
+            version: CACHE_VERSION,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    // Falls back to an empty cache if the file is missing, corrupt or outdated
+    pub fn open<P: AsRef<Path>>(path: P) -> Self {
+        let Ok(data) = std::fs::read(path) else {
+            return Self::new();
+        };
+        match bincode::deserialize::<Self>(&data) {
+            Ok(cache) if cache.version == CACHE_VERSION => cache,
+            _ => Self::new(),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Option<()> {
+        let data = bincode::serialize(self).ok()?;
+        std::fs::write(path, data).ok()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    // Returns the cached data when every input is unchanged
+    pub fn get<P: AsRef<Path>>(&mut self, root: P) -> Option<Option<ModuleDb>> {
+        let root = root.as_ref();
+        let entry = self.entries.get_mut(root)?;
+        let inputs = stamps(root, Some(entry));
+
+        let unchanged = inputs.iter().all(|(file, stamp)| {
+            let cached = entry.inputs.get(file).cloned().flatten();
+            match (stamp, cached) {
+                (Some(stamp), Some(cached)) => {
+                    stamp.size == cached.size && stamp.hash == cached.hash
+                }
+                (None, None) => true,
+                _ => false,
+            }
+        });
+        if !unchanged {
+            return None;
+        }
+
+        // Touched but identical files get their new mtime remembered
+        entry.inputs = inputs;
+        Some(entry.module_db.clone())
+    }
+
+    pub fn from_folder<P: AsRef<Path>>(&mut self, root: P) -> Option<ModuleDb> {
+        let root = root.as_ref();
+        if let Some(module_db) = self.get(root) {
+            return module_db;
+        }
+
+        let inputs = stamps(root, None);
+        let module_db = ModuleDb::from_folder(root);
+        self.entries.insert(
+            root.to_path_buf(),
+            CacheEntry {
+                inputs,
+                module_db: module_db.clone(),
+            },
+        );
+        module_db
+    }
+
+    // Only roots missing from the cache or with changed inputs get parsed
+    pub fn load<P: AsRef<Path> + Sync>(
+        &mut self,
+        batch: &BatchLoad,
+        roots: &[P],
+    ) -> Vec<Option<ModuleDb>> {
+        let mut results = roots.iter().map(|root| self.get(root)).collect::<Vec<_>>();
+
+        let missing = results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| result.is_none())
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let missing_roots = missing
+            .iter()
+            .map(|i| roots[*i].as_ref())
+            .collect::<Vec<_>>();
+        let inputs = missing_roots
+            .iter()
+            .map(|root| stamps(root, None))
+            .collect::<Vec<_>>();
+
+        let loaded = batch.load(&missing_roots);
+        for ((i, inputs), module_db) in missing.into_iter().zip(inputs).zip(loaded) {
+            self.entries.insert(
+                roots[i].as_ref().to_path_buf(),
+                CacheEntry {
+                    inputs,
+                    module_db: module_db.clone(),
+                },
+            );
+            results[i] = Some(module_db);
+        }
+
+        results.into_iter().flatten().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{File, OpenOptions};
+    use std::time::Duration;
+
+    const STRINGS: &str = "lang2/mod_str_array.toml";
+
+    // A copy of the fixture mod's rom folder that the test can modify
+    fn scratch(name: &str) -> PathBuf {
+        let fixture =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mods/fixture_mod/rom");
+        let root =
+            std::env::temp_dir().join(format!("module_db_cache_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for file in input_files() {
+            if fixture.join(&file).exists() {
+                let path = root.join(&file);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::copy(fixture.join(&file), path).unwrap();
+            }
+        }
+        root
+    }
+
+    fn mtime(path: &Path) -> SystemTime {
+        std::fs::metadata(path).unwrap().modified().unwrap()
+    }
+
+    // Moves the mtime away from the cached one, writes can land in the same tick
+    fn touch(path: &Path) {
+        let mtime = mtime(path) + Duration::from_secs(10);
+        OpenOptions::new()
+            .write(true)
+            .open(path)
+            .and_then(|file| File::set_modified(&file, mtime))
+            .unwrap();
+    }
+
+    fn cached(root: &Path) -> Cache {
+        let mut cache = Cache::new();
+        assert!(cache.from_folder(root).is_some());
+        assert!(matches!(cache.get(root), Some(Some(_))));
+        cache
+    }
+
+    #[test]
+    fn size_change_misses() {
+        let root = scratch("size");
+        let mut cache = cached(&root);
+
+        let mut contents = std::fs::read_to_string(root.join(STRINGS)).unwrap();
+        contents.push_str("4 = \"Len fixture\"\n");
+        std::fs::write(root.join(STRINGS), contents).unwrap();
+        assert!(cache.get(&root).is_none());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn same_size_content_change_misses() {
+        let root = scratch("content");
+        let mut cache = cached(&root);
+
+        let contents = std::fs::read_to_string(root.join(STRINGS)).unwrap();
+        let changed = contents.replace("Miku fixture 2", "Miku fixture 9");
+        assert_eq!(changed.len(), contents.len());
+        std::fs::write(root.join(STRINGS), changed).unwrap();
+        touch(&root.join(STRINGS));
+        assert!(cache.get(&root).is_none());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn touch_still_hits() {
+        let root = scratch("touch");
+        let mut cache = cached(&root);
+
+        touch(&root.join(STRINGS));
+        assert!(matches!(cache.get(&root), Some(Some(_))));
+        // The new mtime is remembered so the next lookup doesn't hash again
+        let stamp = cache.entries[&root].inputs[STRINGS].as_ref().unwrap();
+        assert_eq!(stamp.mtime, mtime(&root.join(STRINGS)));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn old_versions_are_dropped() {
+        let root = scratch("version");
+        let path = root.join("cache.bin");
+        let mut cache = cached(&root);
+
+        cache.save(&path).unwrap();
+        assert!(matches!(Cache::open(&path).get(&root), Some(Some(_))));
+
+        cache.version = CACHE_VERSION - 1;
+        cache.save(&path).unwrap();
+        let cache = Cache::open(&path);
+        assert_eq!(cache.version, CACHE_VERSION);
+        assert!(cache.entries.is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{Chara, ItemPart, ItemSub, Language, Localized};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// The layout from before names were Localized, with one name_* field per language.
+// Serializing these gives the same output as older releases did, so costumes leave
+// out everything added to them since.
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Module {
+    pub cos: Costume,
+    pub chara: Chara,
+    pub name: Option<String>,
+    pub name_jp: Option<String>,
+    pub name_en: Option<String>,
+    pub name_cn: Option<String>,
+    pub name_fr: Option<String>,
+    pub name_ge: Option<String>,
+    pub name_it: Option<String>,
+    pub name_kr: Option<String>,
+    pub name_sp: Option<String>,
+    pub name_tw: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Costume {
+    pub id: i32,
+    pub items: Vec<CostumeItem>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CostumeItem {
+    pub id: i32,
+    pub objset: Vec<String>,
+    pub sub: ItemSub,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CustomizeItem {
+    pub bind_module: Option<i32>,
+    pub chara: Chara,
+    pub part: ItemPart,
+    pub obj_id: i32,
+    pub name: Option<String>,
+    pub name_jp: Option<String>,
+    pub name_en: Option<String>,
+    pub name_cn: Option<String>,
+    pub name_fr: Option<String>,
+    pub name_ge: Option<String>,
+    pub name_it: Option<String>,
+    pub name_kr: Option<String>,
+    pub name_sp: Option<String>,
+    pub name_tw: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ModuleDb {
+    pub modules: BTreeMap<i32, Module>,
+    pub cstm_items: BTreeMap<i32, CustomizeItem>,
+}
+
+// In the order of Language::ALL
+pub(crate) fn split(name: &Localized) -> [Option<String>; 10] {
+    Language::ALL.map(|lang| name.get(lang).map(String::from))
+}
+
+pub(crate) fn join(names: [Option<String>; 10]) -> Localized {
+    let mut name = Localized::new();
+    for (lang, value) in Language::ALL.into_iter().zip(names) {
+        name.set(lang, value);
+    }
+    name
+}
+
+impl From<crate::Costume> for Costume {
+    fn from(cos: crate::Costume) -> Self {
+        Self {
+            id: cos.id,
+            items: cos
+                .items
+                .into_iter()
+                .map(|item| CostumeItem {
+                    id: item.id,
+                    objset: item.objset,
+                    sub: item.sub,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<Costume> for crate::Costume {
+    fn from(cos: Costume) -> Self {
+        Self {
+            id: cos.id,
+            items: cos
+                .items
+                .into_iter()
+                .map(|item| crate::CostumeItem {
+                    id: item.id,
+                    objset: item.objset,
+                    sub: item.sub,
+                    objects: Vec::new(),
+                    textures: Vec::new(),
+                })
+                .collect(),
+            missing: false,
+            missing_items: Vec::new(),
+        }
+    }
+}
+
+impl From<crate::Module> for Module {
+    fn from(module: crate::Module) -> Self {
+        let [name, name_jp, name_en, name_cn, name_fr, name_ge, name_it, name_kr, name_sp, name_tw] =
+            split(&module.name);
+        Self {
+            cos: module.cos.into(),
+            chara: module.chara,
+            name,
+            name_jp,
+            name_en,
+            name_cn,
+            name_fr,
+            name_ge,
+            name_it,
+            name_kr,
+            name_sp,
+            name_tw,
+        }
+    }
+}
+
+impl From<Module> for crate::Module {
+    fn from(module: Module) -> Self {
+        Self {
+            cos: module.cos.into(),
+            chara: module.chara,
+            name: join([
+                module.name,
+                module.name_jp,
+                module.name_en,
+                module.name_cn,
+                module.name_fr,
+                module.name_ge,
+                module.name_it,
+                module.name_kr,
+                module.name_sp,
+                module.name_tw,
+            ]),
+        }
+    }
+}
+
+impl From<crate::CustomizeItem> for CustomizeItem {
+    fn from(item: crate::CustomizeItem) -> Self {
+        let [name, name_jp, name_en, name_cn, name_fr, name_ge, name_it, name_kr, name_sp, name_tw] =
+            split(&item.name);
+        Self {
+            bind_module: item.bind_module,
+            chara: item.chara,
+            part: item.part,
+            obj_id: item.obj_id,
+            name,
+            name_jp,
+            name_en,
+            name_cn,
+            name_fr,
+            name_ge,
+            name_it,
+            name_kr,
+            name_sp,
+            name_tw,
+        }
+    }
+}
+
+impl From<CustomizeItem> for crate::CustomizeItem {
+    fn from(item: CustomizeItem) -> Self {
+        Self {
+            bind_module: item.bind_module,
+            chara: item.chara,
+            part: item.part,
+            obj_id: item.obj_id,
+            name: join([
+                item.name,
+                item.name_jp,
+                item.name_en,
+                item.name_cn,
+                item.name_fr,
+                item.name_ge,
+                item.name_it,
+                item.name_kr,
+                item.name_sp,
+                item.name_tw,
+            ]),
+        }
+    }
+}
+
+impl From<crate::ModuleDb> for ModuleDb {
+    fn from(module_db: crate::ModuleDb) -> Self {
+        Self {
+            modules: module_db
+                .modules
+                .into_iter()
+                .map(|(id, module)| (id, module.into()))
+                .collect(),
+            cstm_items: module_db
+                .cstm_items
+                .into_iter()
+                .map(|(id, item)| (id, item.into()))
+                .collect(),
+        }
+    }
+}
+
+impl From<ModuleDb> for crate::ModuleDb {
+    fn from(module_db: ModuleDb) -> Self {
+        Self {
+            modules: module_db
+                .modules
+                .into_iter()
+                .map(|(id, module)| (id, module.into()))
+                .collect(),
+            cstm_items: module_db
+                .cstm_items
+                .into_iter()
+                .map(|(id, item)| (id, item.into()))
+                .collect(),
+        }
+    }
+}
+
+impl crate::ModuleDb {
+    pub fn to_compat(&self) -> ModuleDb {
+        self.clone().into()
+    }
+}
@@ -0,0 +1,531 @@
+use crate::{
+    Chara, CostumeItem, CustomizeItem, ItemPart, ItemSub, Language, Localized, Module, ModuleDb,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleChange {
+    Added,
+    Removed,
+    Renamed {
+        lang: Language,
+        old: Option<String>,
+        new: Option<String>,
+    },
+    Chara {
+        old: Chara,
+        new: Chara,
+    },
+    Cos {
+        old: i32,
+        new: i32,
+    },
+    ItemAdded {
+        item: i32,
+    },
+    ItemRemoved {
+        item: i32,
+    },
+    ItemSub {
+        item: i32,
+        old: ItemSub,
+        new: ItemSub,
+    },
+    Objset {
+        item: i32,
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+    Objects {
+        item: i32,
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+    Textures {
+        item: i32,
+        old: Vec<(String, String)>,
+        new: Vec<(String, String)>,
+    },
+}
+
+#[derive(Serialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CstmItemChange {
+    Added,
+    Removed,
+    Renamed {
+        lang: Language,
+        old: Option<String>,
+        new: Option<String>,
+    },
+    Rebound {
+        old: Option<i32>,
+        new: Option<i32>,
+    },
+    Chara {
+        old: Chara,
+        new: Chara,
+    },
+    Part {
+        old: ItemPart,
+        new: ItemPart,
+    },
+    ObjId {
+        old: i32,
+        new: i32,
+    },
+}
+
+#[derive(Serialize, Clone, Default, PartialEq)]
+pub struct ModuleDbDiff {
+    pub modules: BTreeMap<i32, Vec<ModuleChange>>,
+    pub cstm_items: BTreeMap<i32, Vec<CstmItemChange>>,
+}
+
+fn renames(old: &Localized, new: &Localized) -> Vec<(Language, Option<String>, Option<String>)> {
+    Language::ALL
+        .into_iter()
+        .map(|lang| (lang, old.get(lang), new.get(lang)))
+        .filter(|(_, old, new)| old != new)
+        .map(|(lang, old, new)| (lang, old.map(String::from), new.map(String::from)))
+        .collect()
+}
+
+// Only what chritm_prop says, ids resolved from obj_db and tex_db aren't part of the module
+fn objects(item: &CostumeItem) -> Vec<String> {
+    item.objects.iter().map(|obj| obj.uid.clone()).collect()
+}
+
+fn textures(item: &CostumeItem) -> Vec<(String, String)> {
+    item.textures
+        .iter()
+        .map(|tex| (tex.org.clone(), tex.chg.clone()))
+        .collect()
+}
+
+fn module_changes(old: &Module, new: &Module) -> Vec<ModuleChange> {
+    let mut changes = renames(&old.name, &new.name)
+        .into_iter()
+        .map(|(lang, old, new)| ModuleChange::Renamed { lang, old, new })
+        .collect::<Vec<_>>();
+
+    if old.chara != new.chara {
+        changes.push(ModuleChange::Chara {
+            old: old.chara.clone(),
+            new: new.chara.clone(),
+        });
+    }
+    if old.cos.id != new.cos.id {
+        changes.push(ModuleChange::Cos {
+            old: old.cos.id,
+            new: new.cos.id,
+        });
+    }
+
+    for item in &new.cos.items {
+        let Some(old_item) = old.cos.items.iter().find(|old| old.id == item.id) else {
+            changes.push(ModuleChange::ItemAdded { item: item.id });
+            continue;
+        };
+        if old_item.sub != item.sub {
+            changes.push(ModuleChange::ItemSub {
+                item: item.id,
+                old: old_item.sub.clone(),
+                new: item.sub.clone(),
+            });
+        }
+        if old_item.objset != item.objset {
+            changes.push(ModuleChange::Objset {
+                item: item.id,
+                old: old_item.objset.clone(),
+                new: item.objset.clone(),
+            });
+        }
+        let (old_objects, new_objects) = (objects(old_item), objects(item));
+        if old_objects != new_objects {
+            changes.push(ModuleChange::Objects {
+                item: item.id,
+                old: old_objects,
+                new: new_objects,
+            });
+        }
+        let (old_textures, new_textures) = (textures(old_item), textures(item));
+        if old_textures != new_textures {
+            changes.push(ModuleChange::Textures {
+                item: item.id,
+                old: old_textures,
+                new: new_textures,
+            });
+        }
+    }
+    for item in &old.cos.items {
+        if !new.cos.items.iter().any(|new| new.id == item.id) {
+            changes.push(ModuleChange::ItemRemoved { item: item.id });
+        }
+    }
+
+    changes
+}
+
+fn cstm_item_changes(old: &CustomizeItem, new: &CustomizeItem) -> Vec<CstmItemChange> {
+    let mut changes = renames(&old.name, &new.name)
+        .into_iter()
+        .map(|(lang, old, new)| CstmItemChange::Renamed { lang, old, new })
+        .collect::<Vec<_>>();
+
+    if old.bind_module != new.bind_module {
+        changes.push(CstmItemChange::Rebound {
+            old: old.bind_module,
+            new: new.bind_module,
+        });
+    }
+    if old.chara != new.chara {
+        changes.push(CstmItemChange::Chara {
+            old: old.chara.clone(),
+            new: new.chara.clone(),
+        });
+    }
+    if old.part != new.part {
+        changes.push(CstmItemChange::Part {
+            old: old.part.clone(),
+            new: new.part.clone(),
+        });
+    }
+    if old.obj_id != new.obj_id {
+        changes.push(CstmItemChange::ObjId {
+            old: old.obj_id,
+            new: new.obj_id,
+        });
+    }
+
+    changes
+}
+
+fn diff_maps<T, C>(
+    old: &BTreeMap<i32, T>,
+    new: &BTreeMap<i32, T>,
+    added: C,
+    removed: C,
+    changes: fn(&T, &T) -> Vec<C>,
+) -> BTreeMap<i32, Vec<C>>
+where
+    C: Clone,
+{
+    let mut diff = BTreeMap::new();
+    for (id, new_value) in new {
+        let list = match old.get(id) {
+            Some(old_value) => changes(old_value, new_value),
+            None => vec![added.clone()],
+        };
+        if !list.is_empty() {
+            diff.insert(*id, list);
+        }
+    }
+    for id in old.keys() {
+        if !new.contains_key(id) {
+            diff.insert(*id, vec![removed.clone()]);
+        }
+    }
+    diff
+}
+
+fn quote(name: &Option<String>) -> String {
+    match name {
+        Some(name) => format!("\"{name}\""),
+        None => String::from("none"),
+    }
+}
+
+fn bound(module: &Option<i32>) -> String {
+    match module {
+        Some(module) => module.to_string(),
+        None => String::from("none"),
+    }
+}
+
+fn swaps(textures: &[(String, String)]) -> String {
+    textures
+        .iter()
+        .map(|(org, chg)| format!("{org} => {chg}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn display_name(name: &Localized) -> String {
+    name.get_or_fallback(Language::En)
+        .map(|name| format!(" \"{name}\""))
+        .unwrap_or_default()
+}
+
+impl ModuleDbDiff {
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty() && self.cstm_items.is_empty()
+    }
+
+    // Names come from new, falling back to old for removed entries
+    pub fn changelog(&self, old: &ModuleDb, new: &ModuleDb) -> String {
+        let mut out = Vec::new();
+
+        if !self.modules.is_empty() {
+            out.push(String::from("Modules"));
+        }
+        for (id, changes) in &self.modules {
+            let module = new.modules.get(id).or(old.modules.get(id));
+            let name = module
+                .map(|module| display_name(&module.name))
+                .unwrap_or_default();
+            let chara = module
+                .map(|module| format!(" {}", module.chara.to_string()))
+                .unwrap_or_default();
+
+            match changes.first() {
+                Some(ModuleChange::Added) => out.push(format!("  + {id}{chara}{name}")),
+                Some(ModuleChange::Removed) => out.push(format!("  - {id}{chara}{name}")),
+                _ => out.push(format!("  ~ {id}{chara}{name}")),
+            }
+            for change in changes {
+                let line = match change {
+                    ModuleChange::Added | ModuleChange::Removed => continue,
+                    ModuleChange::Renamed { lang, old, new } => {
+                        format!("name ({}): {} -> {}", lang.code(), quote(old), quote(new))
+                    }
+                    ModuleChange::Chara { old, new } => {
+                        format!("chara: {} -> {}", old.to_string(), new.to_string())
+                    }
+                    ModuleChange::Cos { old, new } => format!("costume: {old} -> {new}"),
+                    ModuleChange::ItemAdded { item } => format!("item {item} added"),
+                    ModuleChange::ItemRemoved { item } => format!("item {item} removed"),
+                    ModuleChange::ItemSub { item, old, new } => {
+                        format!(
+                            "item {item} slot: {} -> {}",
+                            old.to_string(),
+                            new.to_string()
+                        )
+                    }
+                    ModuleChange::Objset { item, old, new } => {
+                        format!(
+                            "item {item} objset: {} -> {}",
+                            old.join(", "),
+                            new.join(", ")
+                        )
+                    }
+                    ModuleChange::Objects { item, old, new } => {
+                        format!(
+                            "item {item} objects: {} -> {}",
+                            old.join(", "),
+                            new.join(", ")
+                        )
+                    }
+                    ModuleChange::Textures { item, old, new } => {
+                        format!("item {item} textures: {} -> {}", swaps(old), swaps(new))
+                    }
+                };
+                out.push(format!("      {line}"));
+            }
+        }
+
+        if !self.cstm_items.is_empty() {
+            if !out.is_empty() {
+                out.push(String::new());
+            }
+            out.push(String::from("Customize items"));
+        }
+        for (id, changes) in &self.cstm_items {
+            let item = new.cstm_items.get(id).or(old.cstm_items.get(id));
+            let name = item
+                .map(|item| display_name(&item.name))
+                .unwrap_or_default();
+            let chara = item
+                .map(|item| format!(" {}", item.chara.to_string()))
+                .unwrap_or_default();
+
+            match changes.first() {
+                Some(CstmItemChange::Added) => out.push(format!("  + {id}{chara}{name}")),
+                Some(CstmItemChange::Removed) => out.push(format!("  - {id}{chara}{name}")),
+                _ => out.push(format!("  ~ {id}{chara}{name}")),
+            }
+            for change in changes {
+                let line = match change {
+                    CstmItemChange::Added | CstmItemChange::Removed => continue,
+                    CstmItemChange::Renamed { lang, old, new } => {
+                        format!("name ({}): {} -> {}", lang.code(), quote(old), quote(new))
+                    }
+                    CstmItemChange::Rebound { old, new } => {
+                        format!("module: {} -> {}", bound(old), bound(new))
+                    }
+                    CstmItemChange::Chara { old, new } => {
+                        format!("chara: {} -> {}", old.to_string(), new.to_string())
+                    }
+                    CstmItemChange::Part { old, new } => {
+                        format!("part: {} -> {}", old.to_string(), new.to_string())
+                    }
+                    CstmItemChange::ObjId { old, new } => format!("obj id: {old} -> {new}"),
+                };
+                out.push(format!("      {line}"));
+            }
+        }
+
+        out.join("\n")
+    }
+}
+
+impl ModuleDb {
+    pub fn diff(&self, new: &ModuleDb) -> ModuleDbDiff {
+        ModuleDbDiff {
+            modules: diff_maps(
+                &self.modules,
+                &new.modules,
+                ModuleChange::Added,
+                ModuleChange::Removed,
+                module_changes,
+            ),
+            cstm_items: diff_maps(
+                &self.cstm_items,
+                &new.cstm_items,
+                CstmItemChange::Added,
+                CstmItemChange::Removed,
+                cstm_item_changes,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build, ItemObject, ObjectId, TextureSwap};
+
+    fn swap(org: &str, chg: &str) -> TextureSwap {
+        TextureSwap {
+            org: org.to_string(),
+            chg: chg.to_string(),
+            org_id: None,
+            chg_id: None,
+        }
+    }
+
+    fn old_db() -> ModuleDb {
+        let mut hair = build::item(1, ItemSub::Kami, &["MIKITM001"]);
+        hair.objects.push(ItemObject {
+            uid: String::from("MIKITM001_KAMI"),
+            id: None,
+        });
+        hair.textures
+            .push(swap("MIKITM001_HAIR", "MIKITM001_HAIR_RED"));
+        let mut miku = build::module(
+            Chara::Miku,
+            0,
+            vec![hair, build::item(2, ItemSub::Te, &["MIKITM002"])],
+        );
+        miku.name.set(Language::En, Some(String::from("Miku")));
+        let mut rin = build::module(Chara::Rin, 0, Vec::new());
+        rin.name.set(Language::En, Some(String::from("Rin")));
+
+        let mut cstm_item = build::cstm_item(Chara::Miku, ItemPart::Kami, 1, None);
+        cstm_item.name.set(Language::En, Some(String::from("Hair")));
+        build::module_db([(1, miku), (2, rin)], [(10, cstm_item)])
+    }
+
+    #[test]
+    fn ids_resolved_from_the_dbs_are_ignored() {
+        let old = old_db();
+        let mut new = old_db();
+        let hair = &mut new.modules.get_mut(&1).unwrap().cos.items[0];
+        hair.objects[0].id = Some(ObjectId { set: 7, id: 1 });
+        hair.textures[0].org_id = Some(3);
+        assert!(old.diff(&new).is_empty());
+    }
+
+    #[test]
+    fn changes_and_changelog() {
+        let old = old_db();
+        let mut new = old_db();
+
+        let miku = new.modules.get_mut(&1).unwrap();
+        miku.name.set(Language::En, Some(String::from("Miku V2")));
+        miku.cos.id = 1;
+        let hair = &mut miku.cos.items[0];
+        hair.objects[0].uid = String::from("MIKITM001_KAMI_V2");
+        hair.textures[0].chg = String::from("MIKITM001_HAIR_BLUE");
+        miku.cos.items[1].sub = ItemSub::UUde;
+        miku.cos
+            .items
+            .push(build::item(3, ItemSub::Outer, &["MIKITM003"]));
+        new.modules.remove(&2);
+        new.modules
+            .insert(3, build::module(Chara::Len, 0, Vec::new()));
+        let cstm_item = new.cstm_items.get_mut(&10).unwrap();
+        cstm_item.bind_module = Some(1);
+        cstm_item.obj_id = 2;
+
+        let diff = old.diff(&new);
+        assert!(
+            diff.modules[&1]
+                == [
+                    ModuleChange::Renamed {
+                        lang: Language::En,
+                        old: Some(String::from("Miku")),
+                        new: Some(String::from("Miku V2")),
+                    },
+                    ModuleChange::Cos { old: 0, new: 1 },
+                    ModuleChange::Objects {
+                        item: 1,
+                        old: vec![String::from("MIKITM001_KAMI")],
+                        new: vec![String::from("MIKITM001_KAMI_V2")],
+                    },
+                    ModuleChange::Textures {
+                        item: 1,
+                        old: vec![(
+                            String::from("MIKITM001_HAIR"),
+                            String::from("MIKITM001_HAIR_RED")
+                        )],
+                        new: vec![(
+                            String::from("MIKITM001_HAIR"),
+                            String::from("MIKITM001_HAIR_BLUE")
+                        )],
+                    },
+                    ModuleChange::ItemSub {
+                        item: 2,
+                        old: ItemSub::Te,
+                        new: ItemSub::UUde,
+                    },
+                    ModuleChange::ItemAdded { item: 3 },
+                ]
+        );
+        assert!(diff.modules[&2] == [ModuleChange::Removed]);
+        assert!(diff.modules[&3] == [ModuleChange::Added]);
+        assert!(
+            diff.cstm_items[&10]
+                == [
+                    CstmItemChange::Rebound {
+                        old: None,
+                        new: Some(1),
+                    },
+                    CstmItemChange::ObjId { old: 1, new: 2 },
+                ]
+        );
+
+        assert_eq!(
+            diff.changelog(&old, &new),
+            concat!(
+                "Modules\n",
+                "  ~ 1 Miku \"Miku V2\"\n",
+                "      name (en): \"Miku\" -> \"Miku V2\"\n",
+                "      costume: 0 -> 1\n",
+                "      item 1 objects: MIKITM001_KAMI -> MIKITM001_KAMI_V2\n",
+                "      item 1 textures: MIKITM001_HAIR => MIKITM001_HAIR_RED -> MIKITM001_HAIR => MIKITM001_HAIR_BLUE\n",
+                "      item 2 slot: Hands (Te) -> Right Arm (Ude)\n",
+                "      item 3 added\n",
+                "  - 2 Rin \"Rin\"\n",
+                "  + 3 Len\n",
+                "\n",
+                "Customize items\n",
+                "  ~ 10 Miku \"Hair\"\n",
+                "      module: none -> 1\n",
+                "      obj id: 1 -> 2",
+            )
+        );
+    }
+}
@@ -4,7 +4,51 @@ use std::collections::BTreeMap;
 #[cfg(feature = "utoipa")]
 use utoipa::ToSchema;
 
+pub mod assets;
+pub mod batch;
+mod binary;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod compat;
+pub mod diff;
+pub mod obj_db;
+#[cfg(feature = "utoipa")]
+pub mod openapi;
 mod parse;
+pub mod po;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "shared")]
+pub mod shared;
+pub mod source;
+pub mod spr_db;
+#[cfg(feature = "csv")]
+mod spreadsheet;
+#[cfg(feature = "thumbnail")]
+pub mod sprite;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+pub mod strings;
+pub mod tex_db;
+#[cfg(feature = "thumbnail")]
+mod texture;
+pub mod translation;
+pub mod validate;
+pub mod vfs;
+#[cfg(feature = "watch")]
+pub mod watch;
+
+#[cfg(feature = "csv")]
+pub use spreadsheet::CsvError;
+
+// The same strings serde uses, MIK for Miku and so on
+#[cfg(any(feature = "csv", feature = "sqlite"))]
+pub(crate) fn serde_name<T: Serialize>(value: &T) -> String {
+    match toml::Value::try_from(value) {
+        Ok(toml::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "utoipa", derive(ToSchema))]
@@ -245,6 +289,22 @@ impl ToString for ItemSub {
 pub struct Costume {
     pub id: i32,
     pub items: Vec<CostumeItem>,
+    // chritm_prop was loaded but has no such costume for the chara
+    #[serde(default)]
+    pub missing: bool,
+    // Items the costume lists that chritm_prop doesn't have, items keeps a
+    // placeholder for each of them
+    #[serde(default)]
+    pub missing_items: Vec<i32>,
+}
+
+impl Costume {
+    // Items without the placeholders standing in for missing ones
+    pub fn resolved_items(&self) -> impl Iterator<Item = &CostumeItem> {
+        self.items
+            .iter()
+            .filter(|item| !self.missing_items.contains(&item.id))
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -253,6 +313,186 @@ pub struct CostumeItem {
     pub id: i32,
     pub objset: Vec<String>,
     pub sub: ItemSub,
+    // The data.obj entries of the chritm item
+    #[serde(default)]
+    pub objects: Vec<ItemObject>,
+    // The data.tex entries of the chritm item
+    #[serde(default)]
+    pub textures: Vec<TextureSwap>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct ItemObject {
+    // Name of the object in obj_db
+    pub uid: String,
+    // Set by ModuleDb::resolve_objects
+    #[serde(default)]
+    pub id: Option<ObjectId>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct ObjectId {
+    pub set: u32,
+    pub id: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct TextureSwap {
+    // The texture being replaced and its replacement, as written in chritm_prop
+    pub org: String,
+    pub chg: String,
+    // Set by ModuleDb::resolve_textures
+    #[serde(default)]
+    pub org_id: Option<u32>,
+    #[serde(default)]
+    pub chg_id: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Language {
+    // The top level tables of mod_str_array.toml
+    Default,
+    // Comes from the game tables rather than mod_str_array.toml
+    Jp,
+    En,
+    Cn,
+    Fr,
+    Ge,
+    It,
+    Kr,
+    Sp,
+    Tw,
+}
+
+impl Language {
+    pub const ALL: [Language; 10] = [
+        Self::Default,
+        Self::Jp,
+        Self::En,
+        Self::Cn,
+        Self::Fr,
+        Self::Ge,
+        Self::It,
+        Self::Kr,
+        Self::Sp,
+        Self::Tw,
+    ];
+
+    // Languages with their own table in mod_str_array.toml
+    pub const TRANSLATED: [Language; 8] = [
+        Self::En,
+        Self::Cn,
+        Self::Fr,
+        Self::Ge,
+        Self::It,
+        Self::Kr,
+        Self::Sp,
+        Self::Tw,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Jp => "jp",
+            Self::En => "en",
+            Self::Cn => "cn",
+            Self::Fr => "fr",
+            Self::Ge => "ge",
+            Self::It => "it",
+            Self::Kr => "kr",
+            Self::Sp => "sp",
+            Self::Tw => "tw",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|lang| lang.code() == code)
+    }
+
+    // The language itself, then the default name, then the japanese one
+    pub fn fallback(&self) -> Vec<Language> {
+        let mut chain = vec![*self];
+        for lang in [Self::Default, Self::Jp] {
+            if !chain.contains(&lang) {
+                chain.push(lang);
+            }
+        }
+        chain
+    }
+}
+
+impl ToString for Language {
+    fn to_string(&self) -> String {
+        String::from(match self {
+            Self::Default => "Default",
+            Self::Jp => "Japanese",
+            Self::En => "English",
+            Self::Cn => "Chinese (Simplified)",
+            Self::Fr => "French",
+            Self::Ge => "German",
+            Self::It => "Italian",
+            Self::Kr => "Korean",
+            Self::Sp => "Spanish",
+            Self::Tw => "Chinese (Traditional)",
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct Localized {
+    names: BTreeMap<Language, String>,
+}
+
+// The derive doesnt follow serde(transparent), describe the inner map instead
+#[cfg(feature = "utoipa")]
+impl utoipa::PartialSchema for Localized {
+    fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
+        <BTreeMap<Language, String> as utoipa::PartialSchema>::schema()
+    }
+}
+
+#[cfg(feature = "utoipa")]
+impl ToSchema for Localized {}
+
+impl Localized {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, lang: Language) -> Option<&str> {
+        self.names.get(&lang).map(|name| name.as_str())
+    }
+
+    // First name found along chain
+    pub fn resolve(&self, chain: &[Language]) -> Option<&str> {
+        chain.iter().find_map(|lang| self.get(*lang))
+    }
+
+    pub fn get_or_fallback(&self, lang: Language) -> Option<&str> {
+        self.resolve(&lang.fallback())
+    }
+
+    // None removes the name
+    pub fn set(&mut self, lang: Language, name: Option<String>) {
+        match name {
+            Some(name) => self.names.insert(lang, name),
+            None => self.names.remove(&lang),
+        };
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Language, &str)> {
+        self.names.iter().map(|(lang, name)| (*lang, name.as_str()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -260,16 +500,7 @@ pub struct CostumeItem {
 pub struct Module {
     pub cos: Costume,
     pub chara: Chara,
-    pub name: Option<String>,
-    pub name_jp: Option<String>,
-    pub name_en: Option<String>,
-    pub name_cn: Option<String>,
-    pub name_fr: Option<String>,
-    pub name_ge: Option<String>,
-    pub name_it: Option<String>,
-    pub name_kr: Option<String>,
-    pub name_sp: Option<String>,
-    pub name_tw: Option<String>,
+    pub name: Localized,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -279,16 +510,7 @@ pub struct CustomizeItem {
     pub chara: Chara,
     pub part: ItemPart,
     pub obj_id: i32,
-    pub name: Option<String>,
-    pub name_jp: Option<String>,
-    pub name_en: Option<String>,
-    pub name_cn: Option<String>,
-    pub name_fr: Option<String>,
-    pub name_ge: Option<String>,
-    pub name_it: Option<String>,
-    pub name_kr: Option<String>,
-    pub name_sp: Option<String>,
-    pub name_tw: Option<String>,
+    pub name: Localized,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -304,6 +526,69 @@ impl ModuleDb {
         gm_customize_item_tbl: Option<P>,
         chritm_prop: Option<P>,
         mod_str_array: Option<P>,
+    ) -> Option<Self> {
+        let gm_module_tbl = gm_module_tbl.and_then(parse::Module::parse);
+        let gm_customize_item_tbl = gm_customize_item_tbl.and_then(parse::CstmItem::parse);
+        // Suboptimal, parsing twice here
+        let chritm_prop = chritm_prop.map(|chritm_prop| {
+            (
+                parse::Costume::parse(&chritm_prop).unwrap_or_default(),
+                parse::CostumeItem::parse(&chritm_prop).unwrap_or_default(),
+            )
+        });
+        let mod_str_array = mod_str_array.and_then(parse::ModStringArray::parse);
+
+        Self::from_tables(
+            gm_module_tbl,
+            gm_customize_item_tbl,
+            chritm_prop,
+            mod_str_array,
+        )
+    }
+
+    pub fn from_bytes(
+        gm_module_tbl: Option<&[u8]>,
+        gm_customize_item_tbl: Option<&[u8]>,
+        chritm_prop: Option<&[u8]>,
+        mod_str_array: Option<&[u8]>,
+    ) -> Option<Self> {
+        Self::from_table_bytes(
+            gm_module_tbl,
+            gm_customize_item_tbl,
+            chritm_prop,
+            mod_str_array.and_then(parse::ModStringArray::from_bytes),
+        )
+    }
+
+    fn from_table_bytes(
+        gm_module_tbl: Option<&[u8]>,
+        gm_customize_item_tbl: Option<&[u8]>,
+        chritm_prop: Option<&[u8]>,
+        mod_str_array: Option<parse::ModStringArray>,
+    ) -> Option<Self> {
+        let gm_module_tbl = gm_module_tbl.and_then(parse::Module::from_bytes);
+        let gm_customize_item_tbl = gm_customize_item_tbl.and_then(parse::CstmItem::from_bytes);
+        let chritm_prop = chritm_prop.map(|chritm_prop| match parse::read_farc(chritm_prop) {
+            Some(farc) => (
+                parse::Costume::from_farc(&farc).unwrap_or_default(),
+                parse::CostumeItem::from_farc(&farc).unwrap_or_default(),
+            ),
+            None => (BTreeMap::new(), BTreeMap::new()),
+        });
+
+        Self::from_tables(
+            gm_module_tbl,
+            gm_customize_item_tbl,
+            chritm_prop,
+            mod_str_array,
+        )
+    }
+
+    fn from_tables(
+        gm_module_tbl: Option<parse::DivaTbl<parse::Module>>,
+        gm_customize_item_tbl: Option<parse::DivaTbl<parse::CstmItem>>,
+        chritm_prop: Option<parse::ChritmProp>,
+        mod_str_array: Option<parse::ModStringArray>,
     ) -> Option<Self> {
         let mut module_db = Self {
             modules: BTreeMap::new(),
@@ -311,73 +596,54 @@ impl ModuleDb {
         };
 
         if let Some(gm_module_tbl) = gm_module_tbl {
-            if let Some(gm_module_tbl) = parse::Module::parse(gm_module_tbl) {
-                for module in gm_module_tbl.data {
-                    let cos = module.cos.trim_start_matches("COS_").parse::<i32>().ok()? - 1;
-                    module_db.modules.insert(
-                        module.id,
-                        Module {
-                            cos: Costume {
-                                id: cos,
-                                items: vec![],
-                            },
-                            chara: module.chara,
-                            name: None,
-                            name_jp: Some(module.name.clone()),
-                            name_en: None,
-                            name_cn: None,
-                            name_fr: None,
-                            name_ge: None,
-                            name_it: None,
-                            name_kr: None,
-                            name_sp: None,
-                            name_tw: None,
+            for module in gm_module_tbl.data {
+                let cos = module.cos.trim_start_matches("COS_").parse::<i32>().ok()? - 1;
+                module_db.modules.insert(
+                    module.id,
+                    Module {
+                        cos: Costume {
+                            id: cos,
+                            items: vec![],
+                            missing: false,
+                            missing_items: vec![],
                         },
-                    );
-                }
+                        chara: module.chara,
+                        name: Localized {
+                            names: BTreeMap::from([(Language::Jp, module.name)]),
+                        },
+                    },
+                );
             }
         }
 
         if let Some(gm_customize_item_tbl) = gm_customize_item_tbl {
-            if let Some(gm_customize_item_tbl) = parse::CstmItem::parse(gm_customize_item_tbl) {
-                for cstm_item in gm_customize_item_tbl.data {
-                    module_db.cstm_items.insert(
-                        cstm_item.id,
-                        CustomizeItem {
-                            bind_module: cstm_item.bind_module,
-                            chara: cstm_item.chara,
-                            part: cstm_item.parts,
-                            obj_id: cstm_item.obj_id,
-                            name: None,
-                            name_jp: Some(cstm_item.name.clone()),
-                            name_en: None,
-                            name_cn: None,
-                            name_fr: None,
-                            name_ge: None,
-                            name_it: None,
-                            name_kr: None,
-                            name_sp: None,
-                            name_tw: None,
+            for cstm_item in gm_customize_item_tbl.data {
+                module_db.cstm_items.insert(
+                    cstm_item.id,
+                    CustomizeItem {
+                        bind_module: cstm_item.bind_module,
+                        chara: cstm_item.chara,
+                        part: cstm_item.parts,
+                        obj_id: cstm_item.obj_id,
+                        name: Localized {
+                            names: BTreeMap::from([(Language::Jp, cstm_item.name)]),
                         },
-                    );
-                }
+                    },
+                );
             }
         }
 
-        if let Some(chritm_prop) = &chritm_prop {
-            // Suboptimal, parsing twice here
-            let modules = parse::Costume::parse(chritm_prop).unwrap_or(BTreeMap::new());
-            let items = parse::CostumeItem::parse(chritm_prop).unwrap_or(BTreeMap::new());
-
+        if let Some((modules, items)) = &chritm_prop {
             for (_, module) in &mut module_db.modules {
                 let Some(costumes) = modules.get(&module.chara) else {
                     println!("Couldnt get costumes for chara");
+                    module.cos.missing = true;
                     continue;
                 };
-                let Some(items) = items.get(&module.chara) else {
-                    println!("Couldnt get items for chara");
-                    continue;
-                };
+                let items = items
+                    .get(&module.chara)
+                    .map(|items| items.data.as_slice())
+                    .unwrap_or_default();
                 let Some(cos) = costumes
                     .data
                     .iter()
@@ -385,16 +651,20 @@ impl ModuleDb {
                     .next()
                 else {
                     println!("Couldnt get costume {}", module.cos.id);
+                    module.cos.missing = true;
                     continue;
                 };
                 for item in &cos.item {
-                    let Some(item) = items.data.iter().filter(|itm| itm.no == *item).next() else {
+                    let Some(item) = items.iter().filter(|itm| itm.no == *item).next() else {
                         println!("Couldnt get item {item} for costume {}", module.cos.id);
+                        module.cos.missing_items.push(*item);
                         // Put in a temporary item that we replace later
                         module.cos.items.push(CostumeItem {
                             id: *item,
                             objset: Vec::new(),
                             sub: ItemSub::Te,
+                            objects: Vec::new(),
+                            textures: Vec::new(),
                         });
                         continue;
                     };
@@ -407,98 +677,21 @@ impl ModuleDb {
         }
 
         if let Some(mod_str_array) = mod_str_array {
-            if let Some(mod_str_array) = parse::ModStringArray::parse(mod_str_array) {
-                for (id, module) in &mut module_db.modules {
-                    if let Some(data) = &mod_str_array.data {
-                        if let Some(modules) = &data.module {
-                            module.name = modules.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.en {
-                        if let Some(modules) = &data.module {
-                            module.name_en = modules.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.cn {
-                        if let Some(modules) = &data.module {
-                            module.name_cn = modules.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.fr {
-                        if let Some(modules) = &data.module {
-                            module.name_fr = modules.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.ge {
-                        if let Some(modules) = &data.module {
-                            module.name_ge = modules.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.it {
-                        if let Some(modules) = &data.module {
-                            module.name_it = modules.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.kr {
-                        if let Some(modules) = &data.module {
-                            module.name_kr = modules.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.sp {
-                        if let Some(modules) = &data.module {
-                            module.name_sp = modules.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.tw {
-                        if let Some(modules) = &data.module {
-                            module.name_tw = modules.get(id).cloned();
+            for lang in Language::ALL {
+                let Some(data) = mod_str_array.get(lang) else {
+                    continue;
+                };
+                if let Some(modules) = &data.module {
+                    for (id, module) in &mut module_db.modules {
+                        if let Some(name) = modules.get(id) {
+                            module.name.set(lang, Some(name.clone()));
                         }
                     }
                 }
-                for (id, cstm_item) in &mut module_db.cstm_items {
-                    if let Some(data) = &mod_str_array.data {
-                        if let Some(customize) = &data.customize {
-                            cstm_item.name = customize.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.en {
-                        if let Some(customize) = &data.customize {
-                            cstm_item.name_en = customize.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.cn {
-                        if let Some(customize) = &data.customize {
-                            cstm_item.name_cn = customize.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.fr {
-                        if let Some(customize) = &data.customize {
-                            cstm_item.name_fr = customize.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.ge {
-                        if let Some(customize) = &data.customize {
-                            cstm_item.name_ge = customize.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.it {
-                        if let Some(customize) = &data.customize {
-                            cstm_item.name_it = customize.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.kr {
-                        if let Some(customize) = &data.customize {
-                            cstm_item.name_kr = customize.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.sp {
-                        if let Some(customize) = &data.customize {
-                            cstm_item.name_sp = customize.get(id).cloned();
-                        }
-                    }
-                    if let Some(data) = &mod_str_array.tw {
-                        if let Some(customize) = &data.customize {
-                            cstm_item.name_tw = customize.get(id).cloned();
+                if let Some(customize) = &data.customize {
+                    for (id, cstm_item) in &mut module_db.cstm_items {
+                        if let Some(name) = customize.get(id) {
+                            cstm_item.name.set(lang, Some(name.clone()));
                         }
                     }
                 }
@@ -512,40 +705,116 @@ impl ModuleDb {
         }
     }
 
+    // Names from every language in the layout of lang2/mod_str_array.toml
+    pub fn to_mod_str_array(&self) -> Option<String> {
+        toml::to_string(&parse::ModStringArray::from_module_db(self)).ok()
+    }
+
+    // Entries from other replace ones with the same id
+    pub fn merge(&mut self, other: Self) {
+        self.modules.extend(other.modules);
+        self.cstm_items.extend(other.cstm_items);
+    }
+
+    pub fn from_source<S: source::TableSource + ?Sized>(source: &S) -> Option<Self> {
+        let module_tbl = source.read(source::Table::GmModule);
+        let customize_tbl = source.read(source::Table::GmCustomizeItem);
+        let chritm_prop = source.read(source::Table::ChritmProp);
+        // Merged from lang2, the legacy lang folder and split per language files
+        let strings = strings::ModStrings::load(source);
+
+        Self::from_table_bytes(
+            module_tbl.as_deref(),
+            customize_tbl.as_deref(),
+            chritm_prop.as_deref(),
+            (!strings.is_empty()).then(|| strings.to_mod_str_array()),
+        )
+    }
+
     pub fn from_folder<P: AsRef<std::path::Path>>(path: P) -> Option<Self> {
         let path = path.as_ref();
         if !path.is_dir() {
             return None;
         }
 
-        let module_tbl = path.join("mod_gm_module_tbl.farc");
-        let module_tbl = if module_tbl.exists() {
-            Some(module_tbl)
-        } else {
-            None
-        };
+        Self::from_source(&source::FolderSource::new(path))
+    }
 
-        let customize_tbl = path.join("mod_gm_customize_item_tbl.farc");
-        let customize_tbl = if customize_tbl.exists() {
-            Some(customize_tbl)
-        } else {
-            None
-        };
+    #[cfg(feature = "zip")]
+    pub fn from_zip<P: AsRef<std::path::Path>>(path: P) -> Option<BTreeMap<String, Self>> {
+        let path = path.as_ref();
+        let archive = source::ZipSource::open(path)?;
 
-        let chritm_prop = path.join("mod_chritm_prop.farc");
-        let chritm_prop = if chritm_prop.exists() {
-            Some(chritm_prop)
-        } else {
-            None
-        };
+        let mut mods = BTreeMap::new();
+        for source in archive.mods() {
+            let Some(module_db) = Self::from_source(&source) else {
+                continue;
+            };
+            let name = match source.root.trim_end_matches('/') {
+                "" => path.file_stem()?.to_string_lossy().to_string(),
+                root => root.to_string(),
+            };
+            mods.insert(name, module_db);
+        }
 
-        let mod_str_array = path.join("lang2").join("mod_str_array.toml");
-        let mod_str_array = if mod_str_array.exists() {
-            Some(mod_str_array)
-        } else {
+        if mods.is_empty() {
             None
-        };
+        } else {
+            Some(mods)
+        }
+    }
+}
 
-        Self::from_files(module_tbl, customize_tbl, chritm_prop, mod_str_array)
+// Hand-made databases for the unit tests
+#[cfg(test)]
+pub(crate) mod build {
+    use super::*;
+
+    pub fn item(id: i32, sub: ItemSub, objset: &[&str]) -> CostumeItem {
+        CostumeItem {
+            id,
+            objset: objset.iter().map(|objset| objset.to_string()).collect(),
+            sub,
+            objects: Vec::new(),
+            textures: Vec::new(),
+        }
+    }
+
+    pub fn module(chara: Chara, cos: i32, items: Vec<CostumeItem>) -> Module {
+        Module {
+            cos: Costume {
+                id: cos,
+                items,
+                missing: false,
+                missing_items: Vec::new(),
+            },
+            chara,
+            name: Localized::new(),
+        }
+    }
+
+    pub fn cstm_item(
+        chara: Chara,
+        part: ItemPart,
+        obj_id: i32,
+        bind_module: Option<i32>,
+    ) -> CustomizeItem {
+        CustomizeItem {
+            bind_module,
+            chara,
+            part,
+            obj_id,
+            name: Localized::new(),
+        }
+    }
+
+    pub fn module_db(
+        modules: impl IntoIterator<Item = (i32, Module)>,
+        cstm_items: impl IntoIterator<Item = (i32, CustomizeItem)>,
+    ) -> ModuleDb {
+        ModuleDb {
+            modules: modules.into_iter().collect(),
+            cstm_items: cstm_items.into_iter().collect(),
+        }
     }
 }
@@ -0,0 +1,345 @@
+use crate::binary::{read_offset, read_string, read_u16, read_u32};
+use crate::source::{FolderSource, TableSource};
+use crate::validate::{Location, Rule, Severity};
+use crate::{ModuleDb, ObjectId};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Serialize, Clone, PartialEq, Eq)]
+pub struct Object {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq)]
+pub struct ObjSet {
+    pub id: u32,
+    pub name: String,
+    pub file_name: String,
+    pub tex_file_name: String,
+    pub archive_file_name: String,
+    pub objects: Vec<Object>,
+}
+
+#[derive(Serialize, Clone, Default)]
+pub struct ObjDb {
+    pub sets: BTreeMap<u32, ObjSet>,
+    #[serde(skip)]
+    set_names: BTreeMap<String, u32>,
+    #[serde(skip)]
+    object_names: BTreeMap<String, ObjectId>,
+}
+
+// Relative to the rom folder, later files override earlier ones
+pub const OBJ_DB_FILES: [&str; 2] = ["objset/obj_db.bin", "objset/mod_obj_db.bin"];
+
+impl ObjDb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let set_count = read_u32(data, 0x00)? as usize;
+        let sets_offset = read_offset(data, 0x08)?;
+        let object_count = read_u32(data, 0x0C)? as usize;
+        let objects_offset = read_offset(data, 0x10)?;
+
+        let mut sets = BTreeMap::new();
+        for i in 0..set_count {
+            let offset = sets_offset + i * 0x24;
+            let set = ObjSet {
+                name: read_string(data, offset)?,
+                id: read_u32(data, offset + 0x04)?,
+                file_name: read_string(data, offset + 0x08)?,
+                tex_file_name: read_string(data, offset + 0x0C)?,
+                archive_file_name: read_string(data, offset + 0x10)?,
+                objects: Vec::new(),
+            };
+            sets.insert(set.id, set);
+        }
+
+        for i in 0..object_count {
+            let offset = objects_offset + i * 0x08;
+            let id = read_u16(data, offset)? as u32;
+            let set = read_u16(data, offset + 0x02)? as u32;
+            let name = read_string(data, offset + 0x04)?;
+            if let Some(set) = sets.get_mut(&set) {
+                set.objects.push(Object { id, name });
+            }
+        }
+
+        let mut obj_db = Self {
+            sets,
+            ..Default::default()
+        };
+        obj_db.index();
+        Some(obj_db)
+    }
+
+    // The base game's obj_db.bin followed by the mod's mod_obj_db.bin
+    pub fn from_source<S: TableSource + ?Sized>(source: &S) -> Option<Self> {
+        let mut obj_db: Option<Self> = None;
+        for file in OBJ_DB_FILES {
+            let Some(other) = source
+                .read_path(file)
+                .and_then(|data| Self::from_bytes(&data))
+            else {
+                continue;
+            };
+            match &mut obj_db {
+                Some(obj_db) => obj_db.merge(other),
+                None => obj_db = Some(other),
+            }
+        }
+        obj_db
+    }
+
+    // Merged over rom folders given lowest priority first
+    pub fn from_folders<P: AsRef<Path>>(folders: &[P]) -> Option<Self> {
+        let mut obj_db: Option<Self> = None;
+        for folder in folders {
+            let Some(other) = Self::from_source(&FolderSource::new(folder.as_ref())) else {
+                continue;
+            };
+            match &mut obj_db {
+                Some(obj_db) => obj_db.merge(other),
+                None => obj_db = Some(other),
+            }
+        }
+        obj_db
+    }
+
+    // Sets in other replace those with the same id
+    pub fn merge(&mut self, other: Self) {
+        self.sets.extend(other.sets);
+        self.index();
+    }
+
+    fn index(&mut self) {
+        self.set_names.clear();
+        self.object_names.clear();
+        for set in self.sets.values() {
+            self.set_names.insert(set.name.to_uppercase(), set.id);
+            for object in &set.objects {
+                self.object_names.insert(
+                    object.name.to_uppercase(),
+                    ObjectId {
+                        set: set.id,
+                        id: object.id,
+                    },
+                );
+            }
+        }
+    }
+
+    pub fn set_by_name(&self, name: &str) -> Option<&ObjSet> {
+        self.sets.get(self.set_names.get(&name.to_uppercase())?)
+    }
+
+    // The farc the set is packed into, without the extension
+    pub fn archive(&self, name: &str) -> Option<&str> {
+        let archive = &self.set_by_name(name)?.archive_file_name;
+        let archive = archive.strip_suffix(".farc").unwrap_or(archive);
+        (!archive.is_empty()).then_some(archive)
+    }
+
+    pub fn object_id(&self, name: &str) -> Option<ObjectId> {
+        self.object_names.get(&name.to_uppercase()).copied()
+    }
+
+    pub fn object(&self, id: ObjectId) -> Option<&Object> {
+        self.sets
+            .get(&id.set)?
+            .objects
+            .iter()
+            .find(|object| object.id == id.id)
+    }
+}
+
+impl ModuleDb {
+    // Fills in the ids of every costume item object obj_db knows about
+    pub fn resolve_objects(&mut self, obj_db: &ObjDb) {
+        for module in self.modules.values_mut() {
+            for item in &mut module.cos.items {
+                for object in &mut item.objects {
+                    object.id = obj_db.object_id(&object.uid);
+                }
+            }
+        }
+    }
+}
+
+// Objsets and objects of costume items missing from the object database
+pub struct ObjDbCheck {
+    pub obj_db: ObjDb,
+}
+
+impl ObjDbCheck {
+    pub fn new(obj_db: ObjDb) -> Self {
+        Self { obj_db }
+    }
+}
+
+impl Rule for ObjDbCheck {
+    fn id(&self) -> &'static str {
+        "unknown-object"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, module_db: &ModuleDb) -> Vec<(Location, String)> {
+        let mut findings = Vec::new();
+        for (id, module) in &module_db.modules {
+            for item in &module.cos.items {
+                let location = Location::CostumeItem {
+                    module: *id,
+                    item: item.id,
+                };
+                for objset in &item.objset {
+                    if self.obj_db.set_by_name(objset).is_none() {
+                        findings.push((location.clone(), format!("Objset {objset} not in obj_db")));
+                    }
+                }
+                for object in &item.objects {
+                    if self.obj_db.object_id(&object.uid).is_none() {
+                        findings.push((
+                            location.clone(),
+                            format!("Object {} not in obj_db", object.uid),
+                        ));
+                    }
+                }
+            }
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::write::{put_string, put_u16, put_u32};
+    use crate::{build, Chara, ItemObject, ItemSub};
+
+    // Two sets with two objects and one object each, and an object of a set
+    // that isn't there
+    fn obj_db_bin() -> Vec<u8> {
+        let mut data = vec![0; 0x7C];
+        put_u32(&mut data, 0x00, 2);
+        put_u32(&mut data, 0x08, 0x14);
+        put_u32(&mut data, 0x0C, 4);
+        put_u32(&mut data, 0x10, 0x5C);
+
+        for (i, (name, id, archive)) in [("MIKITM001", 7, "mikitm001.farc"), ("MIKITM002", 8, "")]
+            .into_iter()
+            .enumerate()
+        {
+            let offset = 0x14 + i * 0x24;
+            let file = name.to_lowercase();
+            put_string(&mut data, offset, name);
+            put_u32(&mut data, offset + 0x04, id);
+            put_string(&mut data, offset + 0x08, &format!("{file}_obj.bin"));
+            put_string(&mut data, offset + 0x0C, &format!("{file}_tex.bin"));
+            put_string(&mut data, offset + 0x10, archive);
+        }
+
+        for (i, (id, set, name)) in [
+            (0, 7, "MIKITM001_ATAM"),
+            (1, 7, "MIKITM001_KAMI"),
+            (0, 8, "MIKITM002_KAMI"),
+            (0, 9, "STRAY"),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let offset = 0x5C + i * 0x08;
+            put_u16(&mut data, offset, id);
+            put_u16(&mut data, offset + 0x02, set);
+            put_string(&mut data, offset + 0x04, name);
+        }
+        data
+    }
+
+    #[test]
+    fn reads_sets_and_objects() {
+        let obj_db = ObjDb::from_bytes(&obj_db_bin()).unwrap();
+        assert_eq!(obj_db.sets.len(), 2);
+
+        let set = obj_db.set_by_name("mikitm001").unwrap();
+        assert_eq!(set.id, 7);
+        assert_eq!(set.file_name, "mikitm001_obj.bin");
+        assert_eq!(set.tex_file_name, "mikitm001_tex.bin");
+        assert_eq!(set.objects.len(), 2);
+        assert_eq!(obj_db.archive("MIKITM001"), Some("mikitm001"));
+
+        let set = obj_db.set_by_name("MIKITM002").unwrap();
+        assert_eq!(set.id, 8);
+        assert_eq!(set.file_name, "mikitm002_obj.bin");
+        assert_eq!(set.objects.len(), 1);
+        assert_eq!(obj_db.archive("MIKITM002"), None);
+
+        let id = obj_db.object_id("mikitm001_kami").unwrap();
+        assert_eq!(id, ObjectId { set: 7, id: 1 });
+        assert_eq!(obj_db.object(id).unwrap().name, "MIKITM001_KAMI");
+        assert_eq!(obj_db.object_id("STRAY"), None);
+    }
+
+    #[test]
+    fn resolves_objects_across_sets() {
+        let obj_db = ObjDb::from_bytes(&obj_db_bin()).unwrap();
+        let mut item = build::item(1, ItemSub::Kami, &["MIKITM001", "MIKITM002"]);
+        item.objects = ["MIKITM001_KAMI", "mikitm002_kami", "STRAY"]
+            .into_iter()
+            .map(|uid| ItemObject {
+                uid: uid.to_string(),
+                id: None,
+            })
+            .collect();
+        let mut module_db = build::module_db([(1, build::module(Chara::Miku, 0, vec![item]))], []);
+
+        module_db.resolve_objects(&obj_db);
+        let ids = module_db.modules[&1].cos.items[0]
+            .objects
+            .iter()
+            .map(|object| object.id)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            [
+                Some(ObjectId { set: 7, id: 1 }),
+                Some(ObjectId { set: 8, id: 0 }),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_is_none() {
+        let data = obj_db_bin();
+        for len in 0..data.len() {
+            assert!(ObjDb::from_bytes(&data[..len]).is_none(), "length {len}");
+        }
+    }
+
+    #[test]
+    fn malformed_is_none() {
+        let mut data = obj_db_bin();
+        put_u32(&mut data, 0x00, u32::MAX);
+        assert!(ObjDb::from_bytes(&data).is_none());
+
+        let mut data = obj_db_bin();
+        put_u32(&mut data, 0x10, u32::MAX);
+        assert!(ObjDb::from_bytes(&data).is_none());
+
+        let mut data = obj_db_bin();
+        put_u32(&mut data, 0x14, u32::MAX);
+        assert!(ObjDb::from_bytes(&data).is_none());
+
+        // Last string without its terminator
+        let mut data = obj_db_bin();
+        data.pop();
+        assert!(ObjDb::from_bytes(&data).is_none());
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{
+    Chara, Costume, CostumeItem, CustomizeItem, ItemObject, ItemPart, ItemSub, Language, Localized,
+    Module, ModuleDb, ObjectId, TextureSwap,
+};
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(components(schemas(
+    ModuleDb,
+    Module,
+    CustomizeItem,
+    Costume,
+    CostumeItem,
+    ItemObject,
+    ObjectId,
+    TextureSwap,
+    Chara,
+    ItemPart,
+    ItemSub,
+    Language,
+    Localized
+)))]
+pub struct SchemaDoc;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    SchemaDoc::openapi()
+}
+
+// OpenAPI 3.1 schemas are JSON Schema 2020-12, only the refs need moving to $defs
+pub fn json_schema() -> serde_json::Value {
+    let components = openapi().components.unwrap_or_default();
+    let defs = serde_json::to_string(&components.schemas)
+        .unwrap_or_default()
+        .replace("#/components/schemas/", "#/$defs/");
+    let defs = serde_json::from_str::<serde_json::Value>(&defs).unwrap_or_default();
+
+    serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$ref": "#/$defs/ModuleDb",
+        "$defs": defs,
+    })
+}
@@ -1,6 +1,6 @@
 use itertools::Itertools;
 use serde::de::{MapAccess, Visitor};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 use std::collections::BTreeMap;
 
@@ -23,6 +23,31 @@ pub struct CostumeItem {
     pub no: i32,
     pub objset: Vec<String>,
     pub sub_id: i32,
+    #[serde(default)]
+    pub data: ItemData,
+}
+
+#[derive(Deserialize, Clone, Default)]
+pub struct ItemData {
+    #[serde(default)]
+    pub obj: Vec<ItemObj>,
+    #[serde(default)]
+    pub tex: Vec<ItemTex>,
+}
+
+// Broken entries come out empty and are dropped, instead of failing the whole table
+#[derive(Deserialize, Clone)]
+pub struct ItemObj {
+    #[serde(default)]
+    pub uid: String,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ItemTex {
+    #[serde(default)]
+    pub org: String,
+    #[serde(default)]
+    pub chg: String,
 }
 
 #[derive(Deserialize, Clone)]
@@ -40,6 +65,11 @@ pub struct DivaTbl<T> {
     pub data: Vec<T>,
 }
 
+pub type ChritmProp = (
+    BTreeMap<crate::Chara, DivaTbl<Costume>>,
+    BTreeMap<crate::Chara, DivaTbl<CostumeItem>>,
+);
+
 struct DivaMapVisitor<T> {
     marker: std::marker::PhantomData<fn() -> T>,
 }
@@ -85,6 +115,20 @@ impl<'de, T: Deserialize<'de>> Deserialize<'de> for DivaTbl<T> {
     }
 }
 
+pub fn is_farc(data: &[u8]) -> bool {
+    matches!(data.get(..4), Some(b"FArc" | b"FArC" | b"FARC"))
+}
+
+pub fn read_farc(data: &[u8]) -> Option<farc::Farc> {
+    farc::Farc::from_buf(data).ok()
+}
+
+fn farc_entry_string(farc: &farc::Farc, name: &str) -> Option<String> {
+    let file = farc.entries.get(name)?;
+    let buf = file.data.to_buf_const()?;
+    String::from_utf8(buf.to_vec()).ok()
+}
+
 pub fn clean_input(input: &str) -> String {
     input
         .lines()
@@ -103,18 +147,30 @@ impl Module {
         }
 
         let str = path.to_str()?;
-        let contents = if str.ends_with("gm_module_tbl.farc") {
+        if str.ends_with("gm_module_tbl.farc") {
             let farc = farc::Farc::from_file(path).ok()?;
-            let file = farc.entries.get("gm_module_id.bin")?;
-            let buf = file.data.to_buf_const()?;
-            String::from_utf8(buf.to_vec()).ok()?
+            Self::from_farc(&farc)
         } else if str.ends_with("gm_module_id.bin") {
-            std::fs::read_to_string(path).ok()?
+            Self::from_contents(&std::fs::read_to_string(path).ok()?)
         } else {
-            return None;
-        };
+            None
+        }
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<DivaTbl<Self>> {
+        if is_farc(data) {
+            Self::from_farc(&read_farc(data)?)
+        } else {
+            Self::from_contents(std::str::from_utf8(data).ok()?)
+        }
+    }
 
-        let data = clean_input(&contents);
+    pub fn from_farc(farc: &farc::Farc) -> Option<DivaTbl<Self>> {
+        Self::from_contents(&farc_entry_string(farc, "gm_module_id.bin")?)
+    }
+
+    pub fn from_contents(contents: &str) -> Option<DivaTbl<Self>> {
+        let data = clean_input(contents);
         if data.len() > 0 {
             serde_divatree::from_str(&data).ok()
         } else {
@@ -135,9 +191,13 @@ impl Costume {
             return None;
         }
 
-        let mut map = BTreeMap::new();
         let farc = farc::Farc::from_file(path).ok()?;
-        for (name, data) in farc.entries {
+        Self::from_farc(&farc)
+    }
+
+    pub fn from_farc(farc: &farc::Farc) -> Option<BTreeMap<crate::Chara, DivaTbl<Self>>> {
+        let mut map = BTreeMap::new();
+        for (name, data) in &farc.entries {
             if !name.ends_with("itm_tbl.txt") {
                 continue;
             }
@@ -191,9 +251,13 @@ impl CostumeItem {
             return None;
         }
 
-        let mut map = BTreeMap::new();
         let farc = farc::Farc::from_file(path).ok()?;
-        for (name, data) in farc.entries {
+        Self::from_farc(&farc)
+    }
+
+    pub fn from_farc(farc: &farc::Farc) -> Option<BTreeMap<crate::Chara, DivaTbl<Self>>> {
+        let mut map = BTreeMap::new();
+        for (name, data) in &farc.entries {
             if !name.ends_with("itm_tbl.txt") {
                 continue;
             }
@@ -245,6 +309,28 @@ impl TryInto<crate::CostumeItem> for CostumeItem {
             id: self.no,
             objset: self.objset,
             sub,
+            objects: self
+                .data
+                .obj
+                .into_iter()
+                .filter(|obj| !obj.uid.is_empty())
+                .map(|obj| crate::ItemObject {
+                    uid: obj.uid,
+                    id: None,
+                })
+                .collect(),
+            textures: self
+                .data
+                .tex
+                .into_iter()
+                .filter(|tex| !tex.org.is_empty() && !tex.chg.is_empty())
+                .map(|tex| crate::TextureSwap {
+                    org: tex.org,
+                    chg: tex.chg,
+                    org_id: None,
+                    chg_id: None,
+                })
+                .collect(),
         })
     }
 }
@@ -257,18 +343,30 @@ impl CstmItem {
         }
 
         let str = path.to_str()?;
-        let contents = if str.ends_with("gm_customize_item_tbl.farc") {
+        if str.ends_with("gm_customize_item_tbl.farc") {
             let farc = farc::Farc::from_file(path).ok()?;
-            let file = farc.entries.get("gm_customize_item_id.bin")?;
-            let buf = file.data.to_buf_const()?;
-            String::from_utf8(buf.to_vec()).ok()?
+            Self::from_farc(&farc)
         } else if str.ends_with("gm_customize_item_id.bin") {
-            std::fs::read_to_string(path).ok()?
+            Self::from_contents(&std::fs::read_to_string(path).ok()?)
         } else {
-            return None;
-        };
+            None
+        }
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<DivaTbl<Self>> {
+        if is_farc(data) {
+            Self::from_farc(&read_farc(data)?)
+        } else {
+            Self::from_contents(std::str::from_utf8(data).ok()?)
+        }
+    }
+
+    pub fn from_farc(farc: &farc::Farc) -> Option<DivaTbl<Self>> {
+        Self::from_contents(&farc_entry_string(farc, "gm_customize_item_id.bin")?)
+    }
 
-        let data = clean_input(&contents)
+    pub fn from_contents(contents: &str) -> Option<DivaTbl<Self>> {
+        let data = clean_input(contents)
             .replace("cstm_item.", "")
             .replace("data_list.", "");
         if data.len() > 0 {
@@ -279,26 +377,36 @@ impl CstmItem {
     }
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct ModStringArray {
     #[serde(flatten)]
     pub data: Option<ModStringArrayData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub en: Option<ModStringArrayData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cn: Option<ModStringArrayData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub fr: Option<ModStringArrayData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub ge: Option<ModStringArrayData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub it: Option<ModStringArrayData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub kr: Option<ModStringArrayData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sp: Option<ModStringArrayData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tw: Option<ModStringArrayData>,
 }
 
 #[serde_as]
-#[derive(Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct ModStringArrayData {
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[serde_as(as = "Option<BTreeMap<DisplayFromStr, _>>")]
     pub module: Option<BTreeMap<i32, String>>,
     #[serde(alias = "cstm_item")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[serde_as(as = "Option<BTreeMap<DisplayFromStr, _>>")]
     pub customize: Option<BTreeMap<i32, String>>,
 }
@@ -311,6 +419,80 @@ impl ModStringArray {
         }
 
         let contents = std::fs::read_to_string(path).ok()?;
-        toml::from_str(&contents).ok()
+        Self::from_contents(&contents)
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        Self::from_contents(std::str::from_utf8(data).ok()?)
+    }
+
+    pub fn from_contents(contents: &str) -> Option<Self> {
+        toml::from_str(contents).ok()
+    }
+
+    pub fn get(&self, lang: crate::Language) -> Option<&ModStringArrayData> {
+        match lang {
+            crate::Language::Default => self.data.as_ref(),
+            crate::Language::Jp => None,
+            crate::Language::En => self.en.as_ref(),
+            crate::Language::Cn => self.cn.as_ref(),
+            crate::Language::Fr => self.fr.as_ref(),
+            crate::Language::Ge => self.ge.as_ref(),
+            crate::Language::It => self.it.as_ref(),
+            crate::Language::Kr => self.kr.as_ref(),
+            crate::Language::Sp => self.sp.as_ref(),
+            crate::Language::Tw => self.tw.as_ref(),
+        }
+    }
+
+    pub fn get_mut(&mut self, lang: crate::Language) -> Option<&mut Option<ModStringArrayData>> {
+        match lang {
+            crate::Language::Default => Some(&mut self.data),
+            crate::Language::Jp => None,
+            crate::Language::En => Some(&mut self.en),
+            crate::Language::Cn => Some(&mut self.cn),
+            crate::Language::Fr => Some(&mut self.fr),
+            crate::Language::Ge => Some(&mut self.ge),
+            crate::Language::It => Some(&mut self.it),
+            crate::Language::Kr => Some(&mut self.kr),
+            crate::Language::Sp => Some(&mut self.sp),
+            crate::Language::Tw => Some(&mut self.tw),
+        }
+    }
+
+    pub fn from_module_db(module_db: &crate::ModuleDb) -> Self {
+        let data = |lang: crate::Language| {
+            let module = module_db
+                .modules
+                .iter()
+                .filter_map(|(id, entry)| Some((*id, entry.name.get(lang)?.to_string())))
+                .collect::<BTreeMap<_, _>>();
+            let customize = module_db
+                .cstm_items
+                .iter()
+                .filter_map(|(id, entry)| Some((*id, entry.name.get(lang)?.to_string())))
+                .collect::<BTreeMap<_, _>>();
+
+            if module.is_empty() && customize.is_empty() {
+                None
+            } else {
+                Some(ModStringArrayData {
+                    module: (!module.is_empty()).then_some(module),
+                    customize: (!customize.is_empty()).then_some(customize),
+                })
+            }
+        };
+
+        Self {
+            data: data(crate::Language::Default),
+            en: data(crate::Language::En),
+            cn: data(crate::Language::Cn),
+            fr: data(crate::Language::Fr),
+            ge: data(crate::Language::Ge),
+            it: data(crate::Language::It),
+            kr: data(crate::Language::Kr),
+            sp: data(crate::Language::Sp),
+            tw: data(crate::Language::Tw),
+        }
     }
 }
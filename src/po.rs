@@ -0,0 +1,452 @@
+use crate::{Language, Localized, ModuleDb};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoError {
+    // 1 based line the entry starts on, 0 when the whole file is refused
+    pub line: u64,
+    pub message: String,
+}
+
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(value: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => out.push('\\'),
+            Some('"') => out.push('"'),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(c) => return Err(format!("Unknown escape \\{c}")),
+            None => return Err(String::from("Unfinished escape")),
+        }
+    }
+    Ok(out)
+}
+
+// Contents of a "..." string without the quotes
+fn quoted(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    let inner = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or_else(|| format!("Expected a quoted string: {value}"))?;
+    unescape(inner)
+}
+
+fn context(kind: &str, id: i32) -> String {
+    format!("{kind}:{id}")
+}
+
+fn parse_context(context: &str) -> Result<(&str, i32), String> {
+    let (kind, id) = context
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid context: {context}"))?;
+    let id = id
+        .parse::<i32>()
+        .map_err(|_| format!("Invalid context: {context}"))?;
+    Ok((kind, id))
+}
+
+// The msgid of a name
+fn source(name: &Localized) -> Option<&str> {
+    name.resolve(&[Language::Jp, Language::Default])
+}
+
+fn write_entry(out: &mut String, comment: &str, context: &str, name: &Localized, lang: Language) {
+    // Untranslatable without a source string, an empty msgid is the header
+    let Some(source) = source(name) else {
+        return;
+    };
+    let translation = name.get(lang).unwrap_or_default();
+
+    out.push_str(&format!("#. {comment}\n"));
+    out.push_str(&format!("msgctxt \"{}\"\n", escape(context)));
+    out.push_str(&format!("msgid \"{}\"\n", escape(source)));
+    out.push_str(&format!("msgstr \"{}\"\n\n", escape(translation)));
+}
+
+#[derive(Default)]
+struct Entry {
+    line: u64,
+    fuzzy: bool,
+    context: Option<String>,
+    id: Option<String>,
+    translation: Option<String>,
+}
+
+enum Field {
+    Context,
+    Id,
+    Translation,
+}
+
+fn parse_entries(po: &str) -> (Vec<Entry>, Vec<PoError>) {
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    let mut entry = Entry::default();
+    let mut field = None;
+    let mut broken = false;
+
+    let mut finish = |entry: &mut Entry, broken: &mut bool| {
+        let entry = std::mem::take(entry);
+        if !std::mem::take(broken) && entry.id.is_some() {
+            entries.push(entry);
+        }
+    };
+
+    for (i, line) in po.lines().enumerate() {
+        let number = i as u64 + 1;
+        let line = line.trim();
+
+        if line.is_empty() {
+            finish(&mut entry, &mut broken);
+            field = None;
+            continue;
+        }
+        // Comments start a new entry once the previous one has its msgstr
+        if let Some(comment) = line.strip_prefix('#') {
+            if entry.translation.is_some() {
+                finish(&mut entry, &mut broken);
+                field = None;
+            }
+            if entry.line == 0 {
+                entry.line = number;
+            }
+            if let Some(flags) = comment.strip_prefix(',') {
+                entry.fuzzy |= flags.split(',').any(|flag| flag.trim() == "fuzzy");
+            }
+            continue;
+        }
+
+        let (next, value) = if let Some(value) = line.strip_prefix("msgctxt ") {
+            (Some(Field::Context), value)
+        } else if let Some(value) = line.strip_prefix("msgid ") {
+            (Some(Field::Id), value)
+        } else if let Some(value) = line.strip_prefix("msgstr ") {
+            (Some(Field::Translation), value)
+        } else if line.starts_with('"') {
+            (None, line)
+        } else {
+            errors.push(PoError {
+                line: number,
+                message: format!("Unexpected line: {line}"),
+            });
+            broken = true;
+            continue;
+        };
+
+        if next.is_some() && entry.translation.is_some() {
+            finish(&mut entry, &mut broken);
+        }
+        if entry.line == 0 {
+            entry.line = number;
+        }
+
+        let value = match quoted(value) {
+            Ok(value) => value,
+            Err(message) => {
+                errors.push(PoError {
+                    line: number,
+                    message,
+                });
+                broken = true;
+                continue;
+            }
+        };
+
+        if let Some(next) = next {
+            field = Some(next);
+            let target = match field {
+                Some(Field::Context) => &mut entry.context,
+                Some(Field::Id) => &mut entry.id,
+                _ => &mut entry.translation,
+            };
+            *target = Some(value);
+            continue;
+        }
+
+        // Continuation of the previous string
+        let target = match field {
+            Some(Field::Context) => &mut entry.context,
+            Some(Field::Id) => &mut entry.id,
+            Some(Field::Translation) => &mut entry.translation,
+            None => {
+                errors.push(PoError {
+                    line: number,
+                    message: String::from("String without a keyword"),
+                });
+                broken = true;
+                continue;
+            }
+        };
+        target.get_or_insert_with(String::new).push_str(&value);
+    }
+    finish(&mut entry, &mut broken);
+
+    (entries, errors)
+}
+
+impl ModuleDb {
+    // One entry per named module and customize item, the japanese or default
+    // name is the source string and the id the context
+    pub fn to_po(&self, lang: Language) -> String {
+        let mut out = String::new();
+        out.push_str("msgid \"\"\nmsgstr \"\"\n");
+        out.push_str("\"Content-Type: text/plain; charset=UTF-8\\n\"\n");
+        out.push_str(&format!("\"Language: {}\\n\"\n\n", lang.code()));
+
+        for (id, module) in &self.modules {
+            let comment = format!("Module {} {}", id, module.chara.to_string());
+            write_entry(
+                &mut out,
+                &comment,
+                &context("module", *id),
+                &module.name,
+                lang,
+            );
+        }
+        for (id, item) in &self.cstm_items {
+            let comment = format!(
+                "Customize item {} {} {}",
+                id,
+                item.chara.to_string(),
+                item.part.to_string()
+            );
+            write_entry(
+                &mut out,
+                &comment,
+                &context("cstm_item", *id),
+                &item.name,
+                lang,
+            );
+        }
+        out
+    }
+
+    // Translated entries replace the names in lang, empty and fuzzy ones are
+    // skipped, as are those made for a source string that has changed since.
+    // to_mod_str_array writes the result back out
+    pub fn merge_po(&mut self, lang: Language, po: &str) -> Vec<PoError> {
+        // The source strings are the msgids, merging into them would replace
+        // them with their translations
+        if !Language::TRANSLATED.contains(&lang) {
+            return vec![PoError {
+                line: 0,
+                message: format!("{} is a source language", lang.to_string()),
+            }];
+        }
+
+        let (entries, mut errors) = parse_entries(po);
+        for entry in entries {
+            // The header
+            if entry.id.as_deref() == Some("") {
+                continue;
+            }
+            let translation = entry.translation.unwrap_or_default();
+            if entry.fuzzy || translation.is_empty() {
+                continue;
+            }
+
+            let Some(context) = entry.context else {
+                errors.push(PoError {
+                    line: entry.line,
+                    message: String::from("Missing msgctxt"),
+                });
+                continue;
+            };
+            let name = match parse_context(&context) {
+                Ok(("module", id)) => self.modules.get_mut(&id).map(|module| &mut module.name),
+                Ok(("cstm_item", id)) => self.cstm_items.get_mut(&id).map(|item| &mut item.name),
+                Ok(_) => {
+                    errors.push(PoError {
+                        line: entry.line,
+                        message: format!("Invalid context: {context}"),
+                    });
+                    continue;
+                }
+                Err(message) => {
+                    errors.push(PoError {
+                        line: entry.line,
+                        message,
+                    });
+                    continue;
+                }
+            };
+
+            let Some(name) = name else {
+                errors.push(PoError {
+                    line: entry.line,
+                    message: format!("No entry for {context}"),
+                });
+                continue;
+            };
+            if source(name) != entry.id.as_deref() {
+                errors.push(PoError {
+                    line: entry.line,
+                    message: format!("Source string of {context} changed, translation skipped"),
+                });
+                continue;
+            }
+            name.set(lang, Some(translation));
+        }
+
+        errors.sort_by_key(|error| error.line);
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build, Chara, ItemPart};
+
+    fn module_db() -> ModuleDb {
+        let mut module = build::module(Chara::Miku, 0, Vec::new());
+        module.name.set(Language::Jp, Some(String::from("ミク")));
+        let mut item = build::cstm_item(Chara::Miku, ItemPart::Kami, 1, Some(1));
+        item.name.set(Language::Jp, Some(String::from("ヘア")));
+        // No source string, so not in the PO
+        let unnamed = build::module(Chara::Rin, 0, Vec::new());
+        build::module_db([(1, module), (2, unnamed)], [(10, item)])
+    }
+
+    fn en(module_db: &ModuleDb, id: i32) -> Option<&str> {
+        module_db.modules[&id].name.get(Language::En)
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut module_db = module_db();
+        let po = module_db.to_po(Language::En);
+        assert!(po.contains("msgctxt \"module:1\"\nmsgid \"ミク\"\nmsgstr \"\"\n"));
+        assert!(!po.contains("module:2"));
+
+        let po = po
+            .replace(
+                "msgid \"ミク\"\nmsgstr \"\"",
+                "msgid \"ミク\"\nmsgstr \"Miku\"",
+            )
+            .replace(
+                "msgid \"ヘア\"\nmsgstr \"\"",
+                "msgid \"ヘア\"\nmsgstr \"Hair\"",
+            );
+        assert_eq!(module_db.merge_po(Language::En, &po), []);
+        assert_eq!(en(&module_db, 1), Some("Miku"));
+        assert_eq!(
+            module_db.cstm_items[&10].name.get(Language::En),
+            Some("Hair")
+        );
+        // Exporting again carries the translations
+        assert!(module_db.to_po(Language::En).contains("msgstr \"Miku\""));
+    }
+
+    #[test]
+    fn multi_line_strings_and_escapes() {
+        let mut module_db = module_db();
+        let po = concat!(
+            "msgctxt \"module:1\"\n",
+            "msgid \"\"\n",
+            "\"ミ\"\n",
+            "\"ク\"\n",
+            "msgstr \"\"\n",
+            "\"Miku \\\"V2\\\"\\n\"\n",
+            "\"\\tback\\\\slash\"\n",
+        );
+        assert_eq!(module_db.merge_po(Language::En, po), []);
+        assert_eq!(en(&module_db, 1), Some("Miku \"V2\"\n\tback\\slash"));
+
+        let po = "msgctxt \"module:1\"\nmsgid \"ミク\"\nmsgstr \"\\q\"\n";
+        let errors = module_db.merge_po(Language::En, po);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 3);
+        assert_eq!(errors[0].message, "Unknown escape \\q");
+    }
+
+    #[test]
+    fn fuzzy_and_empty_are_skipped() {
+        let mut module_db = module_db();
+        let po = concat!(
+            "#, fuzzy\n",
+            "msgctxt \"module:1\"\n",
+            "msgid \"ミク\"\n",
+            "msgstr \"Miku\"\n",
+            "\n",
+            "msgctxt \"cstm_item:10\"\n",
+            "msgid \"ヘア\"\n",
+            "msgstr \"\"\n",
+        );
+        assert_eq!(module_db.merge_po(Language::En, po), []);
+        assert_eq!(en(&module_db, 1), None);
+        assert_eq!(module_db.cstm_items[&10].name.get(Language::En), None);
+    }
+
+    #[test]
+    fn broken_entries_are_reported() {
+        let mut module_db = module_db();
+        let po = concat!(
+            "#. No context\n",
+            "msgid \"ミク\"\n",
+            "msgstr \"Miku\"\n",
+            "\n",
+            "msgctxt \"song:1\"\n",
+            "msgid \"ミク\"\n",
+            "msgstr \"Miku\"\n",
+            "\n",
+            "msgctxt \"module:99\"\n",
+            "msgid \"ミク\"\n",
+            "msgstr \"Miku\"\n",
+            "\n",
+            "msgctxt \"module:1\"\n",
+            "msgid \"古いミク\"\n",
+            "msgstr \"Old Miku\"\n",
+        );
+        let errors = module_db.merge_po(Language::En, po);
+        let errors = errors
+            .iter()
+            .map(|error| (error.line, error.message.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                (1, "Missing msgctxt"),
+                (5, "Invalid context: song:1"),
+                (9, "No entry for module:99"),
+                (13, "Source string of module:1 changed, translation skipped"),
+            ]
+        );
+        assert_eq!(en(&module_db, 1), None);
+    }
+
+    #[test]
+    fn source_languages_are_refused() {
+        let mut module_db = module_db();
+        let po = "msgctxt \"module:1\"\nmsgid \"ミク\"\nmsgstr \"Miku\"\n";
+        for lang in [Language::Jp, Language::Default] {
+            let errors = module_db.merge_po(lang, po);
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].line, 0);
+        }
+        assert_eq!(module_db.modules[&1].name.get(Language::Jp), Some("ミク"));
+        assert_eq!(module_db.modules[&1].name.get(Language::Default), None);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::shared::SharedModuleDb;
+use crate::{Chara, CostumeItem, CustomizeItem, ItemPart, Module, ModuleDb};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(modules, module, cstm_items, cstm_item, costumes, version, reload),
+    components(schemas(CharaCostume))
+)]
+pub struct ApiDoc;
+
+#[derive(Clone)]
+pub struct ServerState {
+    pub module_db: Arc<SharedModuleDb>,
+    // Reloaded from on POST /reload
+    pub mods: Option<PathBuf>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ModuleQuery {
+    pub chara: Option<Chara>,
+    pub id_min: Option<i32>,
+    pub id_max: Option<i32>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CstmItemQuery {
+    pub chara: Option<Chara>,
+    pub part: Option<ItemPart>,
+    pub id_min: Option<i32>,
+    pub id_max: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
+pub struct CharaCostume {
+    pub chara: Chara,
+    pub id: i32,
+    pub items: Vec<CostumeItem>,
+}
+
+impl ServerState {
+    pub fn new(module_db: ModuleDb, mods: Option<PathBuf>) -> Self {
+        Self {
+            module_db: Arc::new(SharedModuleDb::new(module_db)),
+            mods,
+        }
+    }
+
+    // Serves and reloads every mod in a DML mods folder
+    pub fn from_mods_folder<P: Into<PathBuf>>(mods: P) -> Option<Self> {
+        let mods = mods.into();
+        let module_db = ModuleDb::from_mods_folder(&mods)?;
+        Some(Self::new(module_db, Some(mods)))
+    }
+}
+
+fn in_range(id: i32, min: Option<i32>, max: Option<i32>) -> bool {
+    min.is_none_or(|min| id >= min) && max.is_none_or(|max| id <= max)
+}
+
+pub fn router(state: ServerState) -> Router {
+    Router::new()
+        .route("/modules", get(modules))
+        .route("/modules/{id}", get(module))
+        .route("/cstm_items", get(cstm_items))
+        .route("/cstm_items/{id}", get(cstm_item))
+        .route("/costumes", get(costumes))
+        .route("/version", get(version))
+        .route("/reload", post(reload))
+        .route("/openapi.json", get(openapi_json))
+        .with_state(state)
+}
+
+#[utoipa::path(
+    get,
+    path = "/modules",
+    params(ModuleQuery),
+    responses((status = 200, body = BTreeMap<i32, Module>))
+)]
+async fn modules(
+    State(state): State<ServerState>,
+    Query(query): Query<ModuleQuery>,
+) -> Json<BTreeMap<i32, Module>> {
+    let snapshot = state.module_db.load();
+    let modules = snapshot
+        .modules
+        .iter()
+        .filter(|(id, _)| in_range(**id, query.id_min, query.id_max))
+        .filter(|(_, module)| {
+            query
+                .chara
+                .as_ref()
+                .is_none_or(|chara| chara == &module.chara)
+        })
+        .map(|(id, module)| (*id, module.clone()))
+        .collect();
+    Json(modules)
+}
+
+#[utoipa::path(
+    get,
+    path = "/modules/{id}",
+    params(("id" = i32, Path)),
+    responses((status = 200, body = Module), (status = 404))
+)]
+async fn module(
+    State(state): State<ServerState>,
+    Path(id): Path<i32>,
+) -> Result<Json<Module>, StatusCode> {
+    let snapshot = state.module_db.load();
+    let module = snapshot.modules.get(&id).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(module.clone()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/cstm_items",
+    params(CstmItemQuery),
+    responses((status = 200, body = BTreeMap<i32, CustomizeItem>))
+)]
+async fn cstm_items(
+    State(state): State<ServerState>,
+    Query(query): Query<CstmItemQuery>,
+) -> Json<BTreeMap<i32, CustomizeItem>> {
+    let snapshot = state.module_db.load();
+    let cstm_items = snapshot
+        .cstm_items
+        .iter()
+        .filter(|(id, _)| in_range(**id, query.id_min, query.id_max))
+        .filter(|(_, item)| {
+            query
+                .chara
+                .as_ref()
+                .is_none_or(|chara| chara == &item.chara)
+        })
+        .filter(|(_, item)| query.part.as_ref().is_none_or(|part| part == &item.part))
+        .map(|(id, item)| (*id, item.clone()))
+        .collect();
+    Json(cstm_items)
+}
+
+#[utoipa::path(
+    get,
+    path = "/cstm_items/{id}",
+    params(("id" = i32, Path)),
+    responses((status = 200, body = CustomizeItem), (status = 404))
+)]
+async fn cstm_item(
+    State(state): State<ServerState>,
+    Path(id): Path<i32>,
+) -> Result<Json<CustomizeItem>, StatusCode> {
+    let snapshot = state.module_db.load();
+    let item = snapshot.cstm_items.get(&id).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(item.clone()))
+}
+
+// The id range applies to the costume ids, not the module ids
+#[utoipa::path(
+    get,
+    path = "/costumes",
+    params(ModuleQuery),
+    responses((status = 200, body = Vec<CharaCostume>))
+)]
+async fn costumes(
+    State(state): State<ServerState>,
+    Query(query): Query<ModuleQuery>,
+) -> Json<Vec<CharaCostume>> {
+    let snapshot = state.module_db.load();
+    let mut costumes = snapshot
+        .modules
+        .values()
+        .filter(|module| in_range(module.cos.id, query.id_min, query.id_max))
+        .filter(|module| {
+            query
+                .chara
+                .as_ref()
+                .is_none_or(|chara| chara == &module.chara)
+        })
+        .map(|module| CharaCostume {
+            chara: module.chara.clone(),
+            id: module.cos.id,
+            items: module.cos.items.clone(),
+        })
+        .collect::<Vec<_>>();
+    costumes.sort_by(|a, b| (&a.chara, a.id).cmp(&(&b.chara, b.id)));
+    costumes.dedup_by(|a, b| a.chara == b.chara && a.id == b.id);
+    Json(costumes)
+}
+
+#[utoipa::path(get, path = "/version", responses((status = 200, body = u64)))]
+async fn version(State(state): State<ServerState>) -> Json<u64> {
+    Json(state.module_db.version())
+}
+
+#[utoipa::path(
+    post,
+    path = "/reload",
+    responses((status = 200, body = u64), (status = 404), (status = 500))
+)]
+async fn reload(State(state): State<ServerState>) -> Result<Json<u64>, StatusCode> {
+    let mods = state.mods.clone().ok_or(StatusCode::NOT_FOUND)?;
+    let module_db = tokio::task::spawn_blocking(move || ModuleDb::from_mods_folder(mods))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(state.module_db.store(module_db)))
+}
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    let mut openapi = ApiDoc::openapi();
+    openapi.merge(crate::openapi::openapi());
+    openapi
+}
+
+async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(openapi())
+}
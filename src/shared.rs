@@ -0,0 +1,69 @@
+use crate::ModuleDb;
+use arc_swap::ArcSwap;
+use std::sync::{Arc, Mutex};
+
+pub struct Snapshot {
+    // Starts at 0 and goes up by one with every store
+    pub version: u64,
+    pub module_db: ModuleDb,
+}
+
+impl std::ops::Deref for Snapshot {
+    type Target = ModuleDb;
+
+    fn deref(&self) -> &Self::Target {
+        &self.module_db
+    }
+}
+
+// Readers never block, writers are serialized so versions stay in order
+pub struct SharedModuleDb {
+    current: ArcSwap<Snapshot>,
+    writer: Mutex<()>,
+}
+
+impl SharedModuleDb {
+    pub fn new(module_db: ModuleDb) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(Snapshot {
+                version: 0,
+                module_db,
+            }),
+            writer: Mutex::new(()),
+        }
+    }
+
+    pub fn load(&self) -> Arc<Snapshot> {
+        self.current.load_full()
+    }
+
+    pub fn version(&self) -> u64 {
+        self.current.load().version
+    }
+
+    // Returns the version of the new snapshot
+    pub fn store(&self, module_db: ModuleDb) -> u64 {
+        let _guard = self.writer.lock().unwrap_or_else(|err| err.into_inner());
+        let version = self.current.load().version + 1;
+        self.current
+            .store(Arc::new(Snapshot { version, module_db }));
+        version
+    }
+
+    pub fn update<F: FnOnce(&ModuleDb) -> ModuleDb>(&self, f: F) -> u64 {
+        let _guard = self.writer.lock().unwrap_or_else(|err| err.into_inner());
+        let current = self.current.load_full();
+        let version = current.version + 1;
+        self.current.store(Arc::new(Snapshot {
+            version,
+            module_db: f(&current.module_db),
+        }));
+        version
+    }
+}
+
+impl From<ModuleDb> for SharedModuleDb {
+    fn from(module_db: ModuleDb) -> Self {
+        Self::new(module_db)
+    }
+}
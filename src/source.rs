@@ -0,0 +1,231 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Table {
+    GmModule,
+    GmCustomizeItem,
+    ChritmProp,
+    StrArray,
+}
+
+impl Table {
+    pub const ALL: [Table; 4] = [
+        Table::GmModule,
+        Table::GmCustomizeItem,
+        Table::ChritmProp,
+        Table::StrArray,
+    ];
+
+    // Relative to the rom folder of a mod
+    pub fn path(&self) -> &'static str {
+        match self {
+            Self::GmModule => "mod_gm_module_tbl.farc",
+            Self::GmCustomizeItem => "mod_gm_customize_item_tbl.farc",
+            Self::ChritmProp => "mod_chritm_prop.farc",
+            Self::StrArray => "lang2/mod_str_array.toml",
+        }
+    }
+}
+
+pub trait TableSource {
+    fn read(&self, table: Table) -> Option<Vec<u8>>;
+
+    // Any file relative to the rom folder, sources that only know about the
+    // tables can keep the default
+    fn read_path(&self, path: &str) -> Option<Vec<u8>> {
+        let table = Table::ALL.into_iter().find(|table| table.path() == path)?;
+        self.read(table)
+    }
+}
+
+impl<T: TableSource + ?Sized> TableSource for &T {
+    fn read(&self, table: Table) -> Option<Vec<u8>> {
+        (**self).read(table)
+    }
+
+    fn read_path(&self, path: &str) -> Option<Vec<u8>> {
+        (**self).read_path(path)
+    }
+}
+
+impl<T: TableSource + ?Sized> TableSource for Box<T> {
+    fn read(&self, table: Table) -> Option<Vec<u8>> {
+        (**self).read(table)
+    }
+
+    fn read_path(&self, path: &str) -> Option<Vec<u8>> {
+        (**self).read_path(path)
+    }
+}
+
+// Every file ModuleDb::from_source can read, relative to the rom folder
+pub fn input_files() -> Vec<String> {
+    let mut files = Table::ALL
+        .iter()
+        .map(|table| table.path().to_string())
+        .collect::<Vec<_>>();
+    for file in crate::strings::ModStrings::files() {
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    }
+    files
+}
+
+#[derive(Clone)]
+pub struct FolderSource {
+    pub root: PathBuf,
+}
+
+impl FolderSource {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl TableSource for FolderSource {
+    fn read(&self, table: Table) -> Option<Vec<u8>> {
+        self.read_path(table.path())
+    }
+
+    fn read_path(&self, path: &str) -> Option<Vec<u8>> {
+        std::fs::read(self.root.join(path)).ok()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct MemorySource {
+    pub tables: BTreeMap<Table, Vec<u8>>,
+}
+
+impl MemorySource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, table: Table, data: Vec<u8>) -> Self {
+        self.tables.insert(table, data);
+        self
+    }
+}
+
+impl TableSource for MemorySource {
+    fn read(&self, table: Table) -> Option<Vec<u8>> {
+        self.tables.get(&table).cloned()
+    }
+}
+
+// Later layers take priority over earlier ones
+#[derive(Default)]
+pub struct LayeredSource {
+    pub layers: Vec<Box<dyn TableSource>>,
+}
+
+impl LayeredSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<S: TableSource + 'static>(mut self, layer: S) -> Self {
+        self.layers.push(Box::new(layer));
+        self
+    }
+}
+
+impl TableSource for LayeredSource {
+    fn read(&self, table: Table) -> Option<Vec<u8>> {
+        self.layers.iter().rev().find_map(|layer| layer.read(table))
+    }
+
+    fn read_path(&self, path: &str) -> Option<Vec<u8>> {
+        self.layers
+            .iter()
+            .rev()
+            .find_map(|layer| layer.read_path(path))
+    }
+}
+
+#[cfg(feature = "zip")]
+pub struct ZipSource<R> {
+    archive: std::sync::Mutex<zip::ZipArchive<R>>,
+    names: BTreeMap<String, usize>,
+}
+
+#[cfg(feature = "zip")]
+impl ZipSource<std::io::BufReader<std::fs::File>> {
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> Option<Self> {
+        let file = std::fs::File::open(path).ok()?;
+        Self::new(std::io::BufReader::new(file))
+    }
+}
+
+#[cfg(feature = "zip")]
+impl<R: std::io::Read + std::io::Seek> ZipSource<R> {
+    pub fn new(reader: R) -> Option<Self> {
+        let mut archive = zip::ZipArchive::new(reader).ok()?;
+
+        // Some archivers write windows style separators
+        let names = (0..archive.len())
+            .filter_map(|i| {
+                let file = archive.by_index_raw(i).ok()?;
+                Some((file.name().replace('\\', "/"), i))
+            })
+            .collect();
+
+        Some(Self {
+            archive: std::sync::Mutex::new(archive),
+            names,
+        })
+    }
+
+    // Every folder in the archive containing a config.toml
+    pub fn mods(&self) -> Vec<ZipMod<'_, R>> {
+        self.names
+            .keys()
+            .filter_map(|name| {
+                if name == "config.toml" {
+                    Some(String::new())
+                } else {
+                    name.strip_suffix("/config.toml")
+                        .map(|root| format!("{root}/"))
+                }
+            })
+            .map(|root| ZipMod { source: self, root })
+            .collect()
+    }
+
+    pub fn read_file(&self, name: &str) -> Option<Vec<u8>> {
+        use std::io::Read;
+
+        let index = *self.names.get(name)?;
+        let mut archive = self.archive.lock().ok()?;
+        let mut file = archive.by_index(index).ok()?;
+        // The size comes from the archive, don't trust it with a huge allocation
+        let mut buf = Vec::with_capacity(file.size().min(MAX_PREALLOC) as usize);
+        file.read_to_end(&mut buf).ok()?;
+        Some(buf)
+    }
+}
+
+#[cfg(feature = "zip")]
+const MAX_PREALLOC: u64 = 64 * 1024 * 1024;
+
+#[cfg(feature = "zip")]
+pub struct ZipMod<'a, R> {
+    source: &'a ZipSource<R>,
+    // Either empty or ending in a '/'
+    pub root: String,
+}
+
+#[cfg(feature = "zip")]
+impl<R: std::io::Read + std::io::Seek> TableSource for ZipMod<'_, R> {
+    fn read(&self, table: Table) -> Option<Vec<u8>> {
+        self.read_path(table.path())
+    }
+
+    fn read_path(&self, path: &str) -> Option<Vec<u8>> {
+        self.source.read_file(&format!("{}rom/{}", self.root, path))
+    }
+}
@@ -0,0 +1,349 @@
+use crate::binary::{read_offset, read_string, read_u16, read_u32};
+use crate::source::{FolderSource, TableSource};
+use crate::validate::{Location, Rule, Severity};
+use crate::ModuleDb;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Serialize, Clone, PartialEq, Eq)]
+pub struct Sprite {
+    pub id: u32,
+    pub name: String,
+    // Position inside the sprite set file
+    pub index: u32,
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq)]
+pub struct SprSet {
+    pub id: u32,
+    pub name: String,
+    pub file_name: String,
+    pub sprites: Vec<Sprite>,
+    pub textures: Vec<Sprite>,
+}
+
+#[derive(Serialize, Clone, Default)]
+pub struct SprDb {
+    pub sets: BTreeMap<u32, SprSet>,
+    #[serde(skip)]
+    names: BTreeMap<String, u32>,
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq)]
+pub struct Thumbnail {
+    pub set_id: u32,
+    pub set_name: String,
+    pub file_name: String,
+    pub sprite: Sprite,
+}
+
+// Relative to the rom folder, later files override earlier ones
+pub const SPR_DB_FILES: [&str; 2] = ["2d/spr_db.bin", "2d/mod_spr_db.bin"];
+
+// Sprite indices with this bit set refer to the textures of the set
+const TEXTURE_FLAG: u16 = 0x1000;
+
+pub fn module_sprite_set(id: i32) -> String {
+    format!("SPR_SEL_MD{id:03}CMN")
+}
+
+pub fn cstm_item_sprite_set(id: i32) -> String {
+    format!("SPR_CMNITM_THMB{id:03}")
+}
+
+impl SprDb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let set_count = read_u32(data, 0x00)? as usize;
+        let sets_offset = read_offset(data, 0x04)?;
+        let sprite_count = read_u32(data, 0x08)? as usize;
+        let sprites_offset = read_offset(data, 0x0C)?;
+
+        // Sprites point at their set by its position in the file
+        let mut sets = Vec::new();
+        for i in 0..set_count {
+            let offset = sets_offset + i * 0x10;
+            sets.push(SprSet {
+                id: read_u32(data, offset)?,
+                name: read_string(data, offset + 0x04)?,
+                file_name: read_string(data, offset + 0x08)?,
+                sprites: Vec::new(),
+                textures: Vec::new(),
+            });
+        }
+
+        for i in 0..sprite_count {
+            let offset = sprites_offset + i * 0x0C;
+            let sprite = Sprite {
+                id: read_u32(data, offset)?,
+                name: read_string(data, offset + 0x04)?,
+                index: read_u16(data, offset + 0x08)? as u32,
+            };
+            let set_index = read_u16(data, offset + 0x0A)?;
+            let Some(set) = sets.get_mut((set_index & 0xFFF) as usize) else {
+                continue;
+            };
+            if set_index & TEXTURE_FLAG != 0 {
+                set.textures.push(sprite);
+            } else {
+                set.sprites.push(sprite);
+            }
+        }
+
+        let mut spr_db = Self {
+            sets: sets.into_iter().map(|set| (set.id, set)).collect(),
+            ..Default::default()
+        };
+        spr_db.index();
+        Some(spr_db)
+    }
+
+    // The base game's spr_db.bin followed by the mod's mod_spr_db.bin
+    pub fn from_source<S: TableSource + ?Sized>(source: &S) -> Option<Self> {
+        let mut spr_db: Option<Self> = None;
+        for file in SPR_DB_FILES {
+            let Some(other) = source
+                .read_path(file)
+                .and_then(|data| Self::from_bytes(&data))
+            else {
+                continue;
+            };
+            match &mut spr_db {
+                Some(spr_db) => spr_db.merge(other),
+                None => spr_db = Some(other),
+            }
+        }
+        spr_db
+    }
+
+    // Merged over rom folders given lowest priority first
+    pub fn from_folders<P: AsRef<Path>>(folders: &[P]) -> Option<Self> {
+        let mut spr_db: Option<Self> = None;
+        for folder in folders {
+            let Some(other) = Self::from_source(&FolderSource::new(folder.as_ref())) else {
+                continue;
+            };
+            match &mut spr_db {
+                Some(spr_db) => spr_db.merge(other),
+                None => spr_db = Some(other),
+            }
+        }
+        spr_db
+    }
+
+    // Sets in other replace those with the same id
+    pub fn merge(&mut self, other: Self) {
+        self.sets.extend(other.sets);
+        self.index();
+    }
+
+    fn index(&mut self) {
+        self.names = self
+            .sets
+            .values()
+            .map(|set| (set.name.to_uppercase(), set.id))
+            .collect();
+    }
+
+    pub fn set_by_name(&self, name: &str) -> Option<&SprSet> {
+        self.sets.get(self.names.get(&name.to_uppercase())?)
+    }
+
+    // The _IMG sprite of the set, other sprites are backgrounds and frames
+    fn thumbnail(&self, set_name: &str, suffix: &str) -> Option<Thumbnail> {
+        let set = self.set_by_name(set_name)?;
+        let sprite = set
+            .sprites
+            .iter()
+            .find(|sprite| sprite.name.to_uppercase().ends_with(suffix))?;
+        Some(Thumbnail {
+            set_id: set.id,
+            set_name: set.name.clone(),
+            file_name: set.file_name.clone(),
+            sprite: sprite.clone(),
+        })
+    }
+}
+
+impl ModuleDb {
+    pub fn module_thumbnail(&self, id: i32, spr_db: &SprDb) -> Option<Thumbnail> {
+        self.modules.get(&id)?;
+        spr_db.thumbnail(&module_sprite_set(id), "_MD_IMG")
+    }
+
+    pub fn cstm_item_thumbnail(&self, id: i32, spr_db: &SprDb) -> Option<Thumbnail> {
+        self.cstm_items.get(&id)?;
+        spr_db.thumbnail(&cstm_item_sprite_set(id), "_ITM_IMG")
+    }
+
+    pub fn modules_without_thumbnail(&self, spr_db: &SprDb) -> Vec<i32> {
+        self.modules
+            .keys()
+            .filter(|id| self.module_thumbnail(**id, spr_db).is_none())
+            .copied()
+            .collect()
+    }
+}
+
+// Modules and customize items the selector would show without a picture
+pub struct SprDbCheck {
+    pub spr_db: SprDb,
+}
+
+impl SprDbCheck {
+    pub fn new(spr_db: SprDb) -> Self {
+        Self { spr_db }
+    }
+}
+
+impl Rule for SprDbCheck {
+    fn id(&self) -> &'static str {
+        "missing-thumbnail"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, module_db: &ModuleDb) -> Vec<(Location, String)> {
+        let modules = module_db
+            .modules_without_thumbnail(&self.spr_db)
+            .into_iter()
+            .map(|id| {
+                (
+                    Location::Module { id },
+                    format!("No sprite set {} in spr_db", module_sprite_set(id)),
+                )
+            });
+        let cstm_items = module_db
+            .cstm_items
+            .keys()
+            .filter(|id| module_db.cstm_item_thumbnail(**id, &self.spr_db).is_none())
+            .map(|id| {
+                (
+                    Location::CstmItem { id: *id },
+                    format!("No sprite set {} in spr_db", cstm_item_sprite_set(*id)),
+                )
+            });
+        modules.chain(cstm_items).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::write::{put_string, put_u16, put_u32};
+    use crate::{build, Chara, ItemPart};
+
+    // A module set with its _MD_IMG sprite after a background, then a customize
+    // item set with its _ITM_IMG sprite and one texture
+    fn spr_db_bin() -> Vec<u8> {
+        let mut data = vec![0; 0x60];
+        put_u32(&mut data, 0x00, 2);
+        put_u32(&mut data, 0x04, 0x10);
+        put_u32(&mut data, 0x08, 4);
+        put_u32(&mut data, 0x0C, 0x30);
+
+        for (i, (id, name)) in [(40, "SPR_SEL_MD001CMN"), (41, "SPR_CMNITM_THMB010")]
+            .into_iter()
+            .enumerate()
+        {
+            let offset = 0x10 + i * 0x10;
+            put_u32(&mut data, offset, id);
+            put_string(&mut data, offset + 0x04, name);
+            put_string(
+                &mut data,
+                offset + 0x08,
+                &format!("{}.bin", name.to_lowercase()),
+            );
+        }
+
+        for (i, (id, name, index, set)) in [
+            (400, "SPR_SEL_MD001CMN_BG", 0, 0),
+            (401, "SPR_SEL_MD001CMN_MD_IMG", 1, 0),
+            (410, "SPR_CMNITM_THMB010_ITM_IMG", 0, 1),
+            (411, "SPR_CMNITM_THMB010_TEX", 0, TEXTURE_FLAG | 1),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let offset = 0x30 + i * 0x0C;
+            put_u32(&mut data, offset, id);
+            put_string(&mut data, offset + 0x04, name);
+            put_u16(&mut data, offset + 0x08, index);
+            put_u16(&mut data, offset + 0x0A, set);
+        }
+        data
+    }
+
+    #[test]
+    fn reads_sets_sprites_and_textures() {
+        let spr_db = SprDb::from_bytes(&spr_db_bin()).unwrap();
+        let set = spr_db.set_by_name("spr_sel_md001cmn").unwrap();
+        assert_eq!(set.id, 40);
+        assert_eq!(set.file_name, "spr_sel_md001cmn.bin");
+        assert_eq!(set.sprites.len(), 2);
+        assert!(set.textures.is_empty());
+
+        // Set index 1 with and without the texture flag
+        let set = spr_db.set_by_name("SPR_CMNITM_THMB010").unwrap();
+        assert_eq!(set.id, 41);
+        assert_eq!(set.file_name, "spr_cmnitm_thmb010.bin");
+        assert_eq!(set.sprites.len(), 1);
+        assert_eq!(set.sprites[0].id, 410);
+        assert_eq!(set.textures.len(), 1);
+        assert_eq!(set.textures[0].id, 411);
+    }
+
+    #[test]
+    fn thumbnails_of_a_module_db() {
+        let spr_db = SprDb::from_bytes(&spr_db_bin()).unwrap();
+        let module_db = build::module_db(
+            [
+                (1, build::module(Chara::Miku, 0, Vec::new())),
+                (2, build::module(Chara::Miku, 1, Vec::new())),
+            ],
+            [
+                (10, build::cstm_item(Chara::Miku, ItemPart::Kami, 1, None)),
+                (11, build::cstm_item(Chara::Miku, ItemPart::Face, 2, None)),
+            ],
+        );
+
+        let thumbnail = module_db.module_thumbnail(1, &spr_db).unwrap();
+        assert_eq!(thumbnail.set_id, 40);
+        assert_eq!(thumbnail.sprite.id, 401);
+        assert_eq!(thumbnail.sprite.index, 1);
+        let thumbnail = module_db.cstm_item_thumbnail(10, &spr_db).unwrap();
+        assert_eq!(thumbnail.file_name, "spr_cmnitm_thmb010.bin");
+        assert_eq!(thumbnail.sprite.id, 410);
+
+        // Only ids the database has get a thumbnail
+        assert!(module_db.module_thumbnail(3, &spr_db).is_none());
+        assert_eq!(module_db.modules_without_thumbnail(&spr_db), [2]);
+
+        let locations = SprDbCheck::new(spr_db)
+            .check(&module_db)
+            .into_iter()
+            .map(|(location, _)| location)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            locations,
+            [Location::Module { id: 2 }, Location::CstmItem { id: 11 }]
+        );
+    }
+
+    #[test]
+    fn no_thumbnail_without_img_sprite() {
+        let mut data = spr_db_bin();
+        // Rename the _MD_IMG sprite
+        put_string(&mut data, 0x40, "SPR_SEL_MD001CMN_FRAME");
+
+        let spr_db = SprDb::from_bytes(&data).unwrap();
+        assert!(spr_db.set_by_name(&module_sprite_set(1)).is_some());
+        assert!(spr_db.thumbnail(&module_sprite_set(1), "_MD_IMG").is_none());
+    }
+}
@@ -0,0 +1,395 @@
+use crate::compat::{join, split};
+use crate::{
+    serde_name, Chara, Costume, CostumeItem, CustomizeItem, ItemPart, ItemSub, Module, ModuleDb,
+};
+use serde::de::IntoDeserializer;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::io::{Read, Write};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvError {
+    // 1 based line in the file, the header is line 1
+    pub row: u64,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ModuleRow {
+    id: i32,
+    chara: Chara,
+    cos: i32,
+    name: Option<String>,
+    name_jp: Option<String>,
+    name_en: Option<String>,
+    name_cn: Option<String>,
+    name_fr: Option<String>,
+    name_ge: Option<String>,
+    name_it: Option<String>,
+    name_kr: Option<String>,
+    name_sp: Option<String>,
+    name_tw: Option<String>,
+    // Items written as id:SUB:objset,objset and separated by ;
+    items: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CstmItemRow {
+    id: i32,
+    chara: Chara,
+    part: ItemPart,
+    obj_id: i32,
+    bind_module: Option<i32>,
+    name: Option<String>,
+    name_jp: Option<String>,
+    name_en: Option<String>,
+    name_cn: Option<String>,
+    name_fr: Option<String>,
+    name_ge: Option<String>,
+    name_it: Option<String>,
+    name_kr: Option<String>,
+    name_sp: Option<String>,
+    name_tw: Option<String>,
+}
+
+fn format_items(items: &[CostumeItem]) -> String {
+    items
+        .iter()
+        .map(|item| {
+            format!(
+                "{}:{}:{}",
+                item.id,
+                serde_name(&item.sub),
+                item.objset.join(",")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+fn parse_items(items: &str) -> Result<Vec<CostumeItem>, String> {
+    items
+        .split(';')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            let mut parts = item.splitn(3, ':');
+            let id = parts.next().unwrap_or_default().trim();
+            let id = id
+                .parse::<i32>()
+                .map_err(|_| format!("Invalid costume item id: {id}"))?;
+            let sub = parts.next().unwrap_or_default().trim();
+            let sub = ItemSub::deserialize(sub.into_deserializer())
+                .map_err(|_: serde::de::value::Error| format!("Invalid item sub: {sub}"))?;
+            let objset = parts
+                .next()
+                .unwrap_or_default()
+                .split(',')
+                .map(|objset| objset.trim().to_string())
+                .filter(|objset| !objset.is_empty())
+                .collect();
+            Ok(CostumeItem {
+                id,
+                objset,
+                sub,
+                objects: Vec::new(),
+                textures: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+fn read_rows<R: Read, T: for<'de> Deserialize<'de>>(
+    reader: R,
+    mut apply: impl FnMut(T) -> Result<(), String>,
+) -> Vec<CsvError> {
+    let mut reader = csv::Reader::from_reader(reader);
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(err) => {
+            return vec![CsvError {
+                row: 1,
+                message: err.to_string(),
+            }]
+        }
+    };
+
+    let mut errors = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        // Quoted fields can span lines, so count from what the reader saw
+        let position = match &record {
+            Ok(record) => record.position(),
+            Err(err) => err.position(),
+        };
+        let row = position.map_or(i as u64 + 2, |position| position.line());
+        let result = record
+            .map_err(|err| err.to_string())
+            .and_then(|record| {
+                record
+                    .deserialize::<T>(Some(&headers))
+                    .map_err(|err| err.to_string())
+            })
+            .and_then(&mut apply);
+        if let Err(message) = result {
+            errors.push(CsvError { row, message });
+        }
+    }
+    errors
+}
+
+impl ModuleDb {
+    pub fn modules_to_csv<W: Write>(&self, writer: W) -> Result<(), String> {
+        let mut writer = csv::Writer::from_writer(writer);
+        for (id, module) in &self.modules {
+            let [name, name_jp, name_en, name_cn, name_fr, name_ge, name_it, name_kr, name_sp, name_tw] =
+                split(&module.name);
+            writer
+                .serialize(ModuleRow {
+                    id: *id,
+                    chara: module.chara.clone(),
+                    cos: module.cos.id,
+                    name,
+                    name_jp,
+                    name_en,
+                    name_cn,
+                    name_fr,
+                    name_ge,
+                    name_it,
+                    name_kr,
+                    name_sp,
+                    name_tw,
+                    items: format_items(&module.cos.items),
+                })
+                .map_err(|err| err.to_string())?;
+        }
+        writer.flush().map_err(|err| err.to_string())
+    }
+
+    pub fn cstm_items_to_csv<W: Write>(&self, writer: W) -> Result<(), String> {
+        let mut writer = csv::Writer::from_writer(writer);
+        for (id, item) in &self.cstm_items {
+            let [name, name_jp, name_en, name_cn, name_fr, name_ge, name_it, name_kr, name_sp, name_tw] =
+                split(&item.name);
+            writer
+                .serialize(CstmItemRow {
+                    id: *id,
+                    chara: item.chara.clone(),
+                    part: item.part.clone(),
+                    obj_id: item.obj_id,
+                    bind_module: item.bind_module,
+                    name,
+                    name_jp,
+                    name_en,
+                    name_cn,
+                    name_fr,
+                    name_ge,
+                    name_it,
+                    name_kr,
+                    name_sp,
+                    name_tw,
+                })
+                .map_err(|err| err.to_string())?;
+        }
+        writer.flush().map_err(|err| err.to_string())
+    }
+
+    // Rows replace modules with the same id or add new ones, broken rows are skipped
+    pub fn modules_from_csv<R: Read>(&mut self, reader: R) -> Vec<CsvError> {
+        let mut seen = BTreeSet::new();
+        read_rows(reader, |row: ModuleRow| {
+            if !seen.insert(row.id) {
+                return Err(format!("Duplicate module id {}", row.id));
+            }
+            if row.cos < 0 {
+                return Err(format!("Invalid costume id {}", row.cos));
+            }
+            let mut items = parse_items(&row.items)?;
+            // Objects, texture swaps and placeholders aren't columns, keep what the
+            // loader found as long as the costume stays the same
+            let mut missing = false;
+            let mut missing_items = Vec::new();
+            if let Some(module) = self
+                .modules
+                .get(&row.id)
+                .filter(|module| module.cos.id == row.cos)
+            {
+                for item in &mut items {
+                    if let Some(old) = module.cos.items.iter().find(|old| old.id == item.id) {
+                        item.objects = old.objects.clone();
+                        item.textures = old.textures.clone();
+                    }
+                }
+                missing = module.cos.missing;
+                missing_items = module
+                    .cos
+                    .missing_items
+                    .iter()
+                    .filter(|id| items.iter().any(|item| item.id == **id))
+                    .copied()
+                    .collect();
+            }
+
+            self.modules.insert(
+                row.id,
+                Module {
+                    cos: Costume {
+                        id: row.cos,
+                        items,
+                        missing,
+                        missing_items,
+                    },
+                    chara: row.chara,
+                    name: join([
+                        row.name,
+                        row.name_jp,
+                        row.name_en,
+                        row.name_cn,
+                        row.name_fr,
+                        row.name_ge,
+                        row.name_it,
+                        row.name_kr,
+                        row.name_sp,
+                        row.name_tw,
+                    ]),
+                },
+            );
+            Ok(())
+        })
+    }
+
+    pub fn cstm_items_from_csv<R: Read>(&mut self, reader: R) -> Vec<CsvError> {
+        let mut seen = BTreeSet::new();
+        read_rows(reader, |row: CstmItemRow| {
+            if !seen.insert(row.id) {
+                return Err(format!("Duplicate customize item id {}", row.id));
+            }
+
+            self.cstm_items.insert(
+                row.id,
+                CustomizeItem {
+                    bind_module: row.bind_module,
+                    chara: row.chara,
+                    part: row.part,
+                    obj_id: row.obj_id,
+                    name: join([
+                        row.name,
+                        row.name_jp,
+                        row.name_en,
+                        row.name_cn,
+                        row.name_fr,
+                        row.name_ge,
+                        row.name_it,
+                        row.name_kr,
+                        row.name_sp,
+                        row.name_tw,
+                    ]),
+                },
+            );
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build, ItemObject, Language};
+
+    fn modules_csv(module_db: &ModuleDb) -> String {
+        let mut csv = Vec::new();
+        module_db.modules_to_csv(&mut csv).unwrap();
+        String::from_utf8(csv).unwrap()
+    }
+
+    #[test]
+    fn items_column_round_trip() {
+        let mut module = build::module(
+            Chara::Miku,
+            0,
+            vec![
+                build::item(1, ItemSub::Kami, &["MIKITM001"]),
+                build::item(2, ItemSub::Outer, &["MIKITM002", "MIKITM003"]),
+                build::item(3, ItemSub::UUde, &[]),
+            ],
+        );
+        module
+            .name
+            .set(Language::En, Some(String::from("Miku, \"fixture\"")));
+        let module_db = build::module_db([(1, module)], []);
+
+        let csv = modules_csv(&module_db);
+        assert!(csv.contains(",\"1:KAMI:MIKITM001;2:OUTER:MIKITM002,MIKITM003;3:U_UDE:\"\n"));
+
+        let mut imported = build::module_db([], []);
+        assert_eq!(imported.modules_from_csv(csv.as_bytes()), []);
+        assert!(imported.modules == module_db.modules);
+
+        // Spaces and empty entries are fine when written by hand
+        let mut imported = build::module_db([], []);
+        let csv = "id,chara,cos,items\n1,MIK,0,\" 1 : KAMI : MIKITM001 , ; ;2:TE\"\n";
+        assert_eq!(imported.modules_from_csv(csv.as_bytes()), []);
+        let items = &imported.modules[&1].cos.items;
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].objset, ["MIKITM001"]);
+        assert!(items[1].sub == ItemSub::Te && items[1].objset.is_empty());
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let csv = concat!(
+            "id,chara,cos,name,items\n",
+            "1,MIK,0,\"Two\nlines\",1:KAMI:MIKITM001\n",
+            "2,MIK,1,,x:KAMI:MIKITM002\n",
+            "3,MIK,2,,2:NOSE:MIKITM002\n",
+            "1,MIK,3,,\n",
+        );
+        let mut module_db = build::module_db([], []);
+        let errors = module_db.modules_from_csv(csv.as_bytes());
+        assert_eq!(
+            errors,
+            [
+                CsvError {
+                    row: 4,
+                    message: String::from("Invalid costume item id: x"),
+                },
+                CsvError {
+                    row: 5,
+                    message: String::from("Invalid item sub: NOSE"),
+                },
+                CsvError {
+                    row: 6,
+                    message: String::from("Duplicate module id 1"),
+                },
+            ]
+        );
+        // Broken rows are skipped, the others still go in
+        assert_eq!(module_db.modules.keys().collect::<Vec<_>>(), [&1]);
+    }
+
+    #[test]
+    fn loader_state_survives_the_same_costume() {
+        let mut item = build::item(1, ItemSub::Kami, &["MIKITM001"]);
+        item.objects.push(ItemObject {
+            uid: String::from("MIKITM001_KAMI"),
+            id: None,
+        });
+        let mut module =
+            build::module(Chara::Miku, 0, vec![item, build::item(2, ItemSub::Te, &[])]);
+        module.cos.missing_items.push(2);
+        let module_db = build::module_db([(1, module)], []);
+        let csv = modules_csv(&module_db);
+
+        let mut same = module_db.clone();
+        assert_eq!(same.modules_from_csv(csv.as_bytes()), []);
+        assert!(same.modules == module_db.modules);
+
+        // Another costume has other items, so nothing carries over
+        let mut changed = module_db.clone();
+        let csv = csv.replace(",MIK,0,", ",MIK,1,");
+        assert_eq!(changed.modules_from_csv(csv.as_bytes()), []);
+        let cos = &changed.modules[&1].cos;
+        assert_eq!(cos.id, 1);
+        assert!(cos.missing_items.is_empty());
+        assert!(cos.items[0].objects.is_empty());
+    }
+}
@@ -0,0 +1,234 @@
+use crate::binary::{read_f32, read_offset, read_u32};
+use crate::source::TableSource;
+use crate::spr_db::{SprDb, Thumbnail};
+use crate::{parse, texture, ModuleDb};
+use std::path::Path;
+
+// RGBA8, top row first
+#[derive(Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![0; width * height * 4],
+        }
+    }
+
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| (y * self.width + x) * 4)
+    }
+
+    // None outside the image
+    pub fn pixel(&self, x: usize, y: usize) -> Option<[u8; 4]> {
+        let i = self.index(x, y)?;
+        self.data.get(i..i + 4)?.try_into().ok()
+    }
+
+    // Pixels outside the image are ignored
+    pub fn set_pixel(&mut self, x: usize, y: usize, pixel: [u8; 4]) {
+        if let Some(i) = self.index(x, y) {
+            self.data[i..i + 4].copy_from_slice(&pixel);
+        }
+    }
+
+    pub fn flip(&mut self) {
+        let row = self.width * 4;
+        for y in 0..self.height / 2 {
+            let (top, bottom) = self.data.split_at_mut((self.height - y - 1) * row);
+            top[y * row..(y + 1) * row].swap_with_slice(&mut bottom[..row]);
+        }
+    }
+
+    // None when the rectangle doesn't fit
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Option<Self> {
+        if x.checked_add(width)? > self.width || y.checked_add(height)? > self.height {
+            return None;
+        }
+        let mut image = Self::new(width, height);
+        for row in 0..height {
+            let start = ((y + row) * self.width + x) * 4;
+            image.data[row * width * 4..(row + 1) * width * 4]
+                .copy_from_slice(&self.data[start..start + width * 4]);
+        }
+        Some(image)
+    }
+
+    pub fn to_png(&self) -> Option<Vec<u8>> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().ok()?;
+        writer.write_image_data(&self.data).ok()?;
+        writer.finish().ok()?;
+        Some(png)
+    }
+
+    pub fn write_png<P: AsRef<Path>>(&self, path: P) -> Option<()> {
+        std::fs::write(path, self.to_png()?).ok()
+    }
+}
+
+// Pixel rectangle of a sprite in its texture
+#[derive(Clone, Copy, PartialEq)]
+pub struct SpriteRect {
+    pub texture: usize,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+// Relative to the rom folder, the farc holds the set's file under its own name
+pub fn sprite_farc_path(file_name: &str) -> String {
+    let name = file_name.to_lowercase();
+    let name = name.strip_suffix(".bin").unwrap_or(&name);
+    format!("2d/{name}.farc")
+}
+
+fn read_rects(data: &[u8]) -> Option<Vec<SpriteRect>> {
+    let count = read_u32(data, 0x0C)? as usize;
+    let offset = read_offset(data, 0x10)?;
+    (0..count)
+        .map(|i| {
+            let offset = offset + i * 0x28;
+            Some(SpriteRect {
+                texture: read_u32(data, offset)? as usize,
+                x: read_f32(data, offset + 0x18)?,
+                y: read_f32(data, offset + 0x1C)?,
+                width: read_f32(data, offset + 0x20)?,
+                height: read_f32(data, offset + 0x24)?,
+            })
+        })
+        .collect()
+}
+
+// Decodes sprite index of a sprite set file as found inside its farc
+pub fn decode_sprite(data: &[u8], index: usize) -> Option<Image> {
+    let rect = *read_rects(data)?.get(index)?;
+    let textures = data.get(read_offset(data, 0x04)?..)?;
+    let textures = texture::read_texture_set(textures)?;
+    let mut image = textures.get(rect.texture)?.decode()?;
+    // Rectangles count from the top, textures from the bottom
+    image.flip();
+    image.crop(
+        rect.x.max(0.0) as usize,
+        rect.y.max(0.0) as usize,
+        rect.width.max(0.0) as usize,
+        rect.height.max(0.0) as usize,
+    )
+}
+
+// Reads the sprite farc through source, a FolderSource on rom or a LayeredSource
+// to fall back to the game and other mods
+pub fn read_thumbnail<S: TableSource + ?Sized>(source: &S, thumbnail: &Thumbnail) -> Option<Image> {
+    let farc = parse::read_farc(&source.read_path(&sprite_farc_path(&thumbnail.file_name))?)?;
+    let entry = farc
+        .entries
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(&thumbnail.file_name))?
+        .1;
+    decode_sprite(entry.data.to_buf_const()?, thumbnail.sprite.index as usize)
+}
+
+impl ModuleDb {
+    pub fn module_png<S: TableSource + ?Sized>(
+        &self,
+        id: i32,
+        spr_db: &SprDb,
+        source: &S,
+    ) -> Option<Vec<u8>> {
+        read_thumbnail(source, &self.module_thumbnail(id, spr_db)?)?.to_png()
+    }
+
+    pub fn cstm_item_png<S: TableSource + ?Sized>(
+        &self,
+        id: i32,
+        spr_db: &SprDb,
+        source: &S,
+    ) -> Option<Vec<u8>> {
+        read_thumbnail(source, &self.cstm_item_thumbnail(id, spr_db)?)?.to_png()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::write::put_u32;
+
+    // One 4x4 RGBA8 texture stored bottom row first, every pixel holds its
+    // stored row and column, and one sprite covering the middle 2x2
+    fn sprite_bin() -> Vec<u8> {
+        let mut data = vec![0; 0x3C];
+        put_u32(&mut data, 0x04, 0x3C);
+        put_u32(&mut data, 0x0C, 1);
+        put_u32(&mut data, 0x10, 0x14);
+        for (offset, value) in [(0x2C, 1.0f32), (0x30, 1.0), (0x34, 2.0), (0x38, 2.0)] {
+            put_u32(&mut data, offset, value.to_bits());
+        }
+
+        let mut set = vec![0; 0x10];
+        put_u32(&mut set, 0x00, 0x03505854);
+        put_u32(&mut set, 0x04, 1);
+        put_u32(&mut set, 0x0C, 0x10);
+
+        let mut texture = vec![0; 0x10];
+        put_u32(&mut texture, 0x04, 1);
+        put_u32(&mut texture, 0x08, 1);
+        put_u32(&mut texture, 0x0C, 0x10);
+
+        let mut mipmap = vec![0; 0x18];
+        put_u32(&mut mipmap, 0x00, 0x02505854);
+        put_u32(&mut mipmap, 0x04, 4);
+        put_u32(&mut mipmap, 0x08, 4);
+        put_u32(&mut mipmap, 0x0C, 2);
+        put_u32(&mut mipmap, 0x14, 64);
+        for row in 0..4 {
+            for column in 0..4 {
+                mipmap.extend_from_slice(&[row, column, 0, 0xFF]);
+            }
+        }
+
+        [data, set, texture, mipmap].concat()
+    }
+
+    #[test]
+    fn decode_flips_and_crops() {
+        let image = decode_sprite(&sprite_bin(), 0).unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+        // The top row of the sprite is the third row of the flipped texture
+        assert_eq!(image.pixel(0, 0), Some([2, 1, 0, 0xFF]));
+        assert_eq!(image.pixel(1, 0), Some([2, 2, 0, 0xFF]));
+        assert_eq!(image.pixel(0, 1), Some([1, 1, 0, 0xFF]));
+        assert_eq!(image.pixel(1, 1), Some([1, 2, 0, 0xFF]));
+        assert_eq!(image.pixel(2, 0), None);
+    }
+
+    #[test]
+    fn bad_sprites_are_none() {
+        let data = sprite_bin();
+        assert!(decode_sprite(&data, 1).is_none());
+
+        // Rectangle past the edge of the texture
+        let mut bad = data.clone();
+        put_u32(&mut bad, 0x34, 4.0f32.to_bits());
+        assert!(decode_sprite(&bad, 0).is_none());
+
+        // Texture past the end of the texture set
+        let mut bad = data.clone();
+        put_u32(&mut bad, 0x14, 1);
+        assert!(decode_sprite(&bad, 0).is_none());
+
+        // Texture set past the end of the file
+        let mut bad = data.clone();
+        put_u32(&mut bad, 0x04, data.len() as u32);
+        assert!(decode_sprite(&bad, 0).is_none());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{serde_name, Chara, ModuleDb};
+use rusqlite::params;
+
+// Foreign keys are declared but not enforced during export, customize items and
+// modules can refer to base game entries that aren't part of the database
+const SCHEMA: &str = "
+DROP TABLE IF EXISTS names;
+DROP TABLE IF EXISTS customize_items;
+DROP TABLE IF EXISTS objsets;
+DROP TABLE IF EXISTS costume_items;
+DROP TABLE IF EXISTS modules;
+DROP TABLE IF EXISTS costumes;
+DROP TABLE IF EXISTS charas;
+
+CREATE TABLE charas (
+    code TEXT PRIMARY KEY,
+    name TEXT NOT NULL
+);
+
+CREATE TABLE costumes (
+    chara TEXT NOT NULL REFERENCES charas(code),
+    id INTEGER NOT NULL,
+    PRIMARY KEY (chara, id)
+);
+
+CREATE TABLE modules (
+    id INTEGER PRIMARY KEY,
+    chara TEXT NOT NULL REFERENCES charas(code),
+    cos INTEGER NOT NULL,
+    FOREIGN KEY (chara, cos) REFERENCES costumes(chara, id)
+);
+
+CREATE TABLE costume_items (
+    chara TEXT NOT NULL,
+    costume INTEGER NOT NULL,
+    item INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    sub TEXT NOT NULL,
+    PRIMARY KEY (chara, costume, item),
+    FOREIGN KEY (chara, costume) REFERENCES costumes(chara, id)
+);
+
+CREATE TABLE objsets (
+    chara TEXT NOT NULL,
+    costume INTEGER NOT NULL,
+    item INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (chara, costume, item, position),
+    FOREIGN KEY (chara, costume, item) REFERENCES costume_items(chara, costume, item)
+);
+
+CREATE TABLE customize_items (
+    id INTEGER PRIMARY KEY,
+    chara TEXT NOT NULL REFERENCES charas(code),
+    part TEXT NOT NULL,
+    obj_id INTEGER NOT NULL,
+    bind_module INTEGER REFERENCES modules(id)
+);
+
+CREATE TABLE names (
+    module INTEGER REFERENCES modules(id),
+    cstm_item INTEGER REFERENCES customize_items(id),
+    lang TEXT NOT NULL,
+    name TEXT NOT NULL,
+    CHECK ((module IS NULL) != (cstm_item IS NULL))
+);
+
+CREATE INDEX names_module ON names(module);
+CREATE INDEX names_cstm_item ON names(cstm_item);
+CREATE INDEX customize_items_bind_module ON customize_items(bind_module);
+";
+
+const CHARAS: [Chara; 12] = [
+    Chara::Miku,
+    Chara::Rin,
+    Chara::Len,
+    Chara::Luka,
+    Chara::Neru,
+    Chara::Haku,
+    Chara::Kaito,
+    Chara::Meiko,
+    Chara::Sakine,
+    Chara::Teto,
+    Chara::Extra,
+    Chara::All,
+];
+
+impl ModuleDb {
+    // Replaces the module_db tables in the file, other tables are left alone
+    pub fn to_sqlite<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), String> {
+        let mut conn = rusqlite::Connection::open(path).map_err(|err| err.to_string())?;
+        self.write_sqlite(&mut conn).map_err(|err| err.to_string())
+    }
+
+    fn write_sqlite(&self, conn: &mut rusqlite::Connection) -> rusqlite::Result<()> {
+        let tx = conn.transaction()?;
+        tx.execute_batch(SCHEMA)?;
+
+        {
+            let mut insert_chara = tx.prepare("INSERT INTO charas (code, name) VALUES (?1, ?2)")?;
+            for chara in &CHARAS {
+                insert_chara.execute(params![serde_name(chara), chara.to_string()])?;
+            }
+
+            let mut insert_costume =
+                tx.prepare("INSERT OR IGNORE INTO costumes (chara, id) VALUES (?1, ?2)")?;
+            let mut insert_module =
+                tx.prepare("INSERT INTO modules (id, chara, cos) VALUES (?1, ?2, ?3)")?;
+            let mut insert_item = tx.prepare(
+                "INSERT OR IGNORE INTO costume_items (chara, costume, item, position, sub)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            let mut insert_objset = tx.prepare(
+                "INSERT OR IGNORE INTO objsets (chara, costume, item, position, name)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            let mut insert_name = tx.prepare(
+                "INSERT INTO names (module, cstm_item, lang, name) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+
+            for (id, module) in &self.modules {
+                let chara = serde_name(&module.chara);
+                insert_costume.execute(params![chara, module.cos.id])?;
+                insert_module.execute(params![id, chara, module.cos.id])?;
+
+                for (position, item) in module.cos.items.iter().enumerate() {
+                    insert_item.execute(params![
+                        chara,
+                        module.cos.id,
+                        item.id,
+                        position,
+                        serde_name(&item.sub)
+                    ])?;
+                    for (position, objset) in item.objset.iter().enumerate() {
+                        insert_objset.execute(params![
+                            chara,
+                            module.cos.id,
+                            item.id,
+                            position,
+                            objset
+                        ])?;
+                    }
+                }
+
+                for (lang, name) in module.name.iter() {
+                    insert_name.execute(params![id, None::<i32>, lang.code(), name])?;
+                }
+            }
+
+            let mut insert_cstm_item = tx.prepare(
+                "INSERT INTO customize_items (id, chara, part, obj_id, bind_module)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for (id, item) in &self.cstm_items {
+                // Negative ids mean the item isn't bound to a module
+                let bind_module = item.bind_module.filter(|module| *module >= 0);
+                insert_cstm_item.execute(params![
+                    id,
+                    serde_name(&item.chara),
+                    serde_name(&item.part),
+                    item.obj_id,
+                    bind_module
+                ])?;
+
+                for (lang, name) in item.name.iter() {
+                    insert_name.execute(params![None::<i32>, id, lang.code(), name])?;
+                }
+            }
+        }
+
+        tx.commit()
+    }
+}
@@ -0,0 +1,247 @@
+use crate::parse::{ModStringArray, ModStringArrayData};
+use crate::source::{Table, TableSource};
+use crate::Language;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum StringKind {
+    Module,
+    CstmItem,
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq)]
+pub struct StringEntry {
+    pub value: String,
+    // Relative to the rom folder
+    pub file: String,
+}
+
+// Every string of a mod merged from all the files it ships
+#[derive(Serialize, Clone, Default)]
+pub struct ModStrings {
+    pub strings: BTreeMap<Language, BTreeMap<StringKind, BTreeMap<i32, StringEntry>>>,
+}
+
+fn folder_files(folder: &str) -> Vec<(String, Option<Language>)> {
+    let mut files = vec![(format!("{folder}/mod_str_array.toml"), None)];
+    for lang in Language::TRANSLATED {
+        files.push((
+            format!("{folder}/mod_str_array_{}.toml", lang.code()),
+            Some(lang),
+        ));
+    }
+    files
+}
+
+// Lowest precedence first. DivaModLoader itself only reads
+// rom/lang2/mod_str_array.toml (Source/DivaModLoader/StrArray.cpp), the legacy
+// lang folder and the split mod_str_array_<lang>.toml files are what older
+// mods ship. lang2 beats lang, and inside a folder the split files beat the
+// combined one. Split files hold the [module] and [customize] tables of their
+// language
+fn locations() -> Vec<(String, Option<Language>)> {
+    let mut files = folder_files("lang");
+    files.extend(folder_files("lang2"));
+    files
+}
+
+impl ModStrings {
+    pub fn files() -> Vec<String> {
+        locations().into_iter().map(|(file, _)| file).collect()
+    }
+
+    pub fn load<S: TableSource + ?Sized>(source: &S) -> Self {
+        let mut strings = Self::default();
+        for (file, lang) in locations() {
+            let data = if file == Table::StrArray.path() {
+                source.read(Table::StrArray)
+            } else {
+                source.read_path(&file)
+            };
+            let Some(data) = data else {
+                continue;
+            };
+            let Some(contents) = std::str::from_utf8(&data).ok() else {
+                continue;
+            };
+
+            match lang {
+                Some(lang) => {
+                    if let Ok(data) = toml::from_str::<ModStringArrayData>(contents) {
+                        strings.insert(lang, &data, &file);
+                    }
+                }
+                None => {
+                    let Some(mod_str_array) = ModStringArray::from_contents(contents) else {
+                        continue;
+                    };
+                    for lang in Language::ALL {
+                        if let Some(data) = mod_str_array.get(lang) {
+                            strings.insert(lang, data, &file);
+                        }
+                    }
+                }
+            }
+        }
+        strings
+    }
+
+    fn insert(&mut self, lang: Language, data: &ModStringArrayData, file: &str) {
+        let tables = [
+            (StringKind::Module, &data.module),
+            (StringKind::CstmItem, &data.customize),
+        ];
+        for (kind, table) in tables {
+            let Some(table) = table else {
+                continue;
+            };
+            let entries = self
+                .strings
+                .entry(lang)
+                .or_default()
+                .entry(kind)
+                .or_default();
+            for (id, value) in table {
+                entries.insert(
+                    *id,
+                    StringEntry {
+                        value: value.clone(),
+                        file: file.to_string(),
+                    },
+                );
+            }
+        }
+    }
+
+    pub fn get(&self, lang: Language, kind: StringKind, id: i32) -> Option<&StringEntry> {
+        self.strings.get(&lang)?.get(&kind)?.get(&id)
+    }
+
+    // The file the string was taken from
+    pub fn origin(&self, lang: Language, kind: StringKind, id: i32) -> Option<&str> {
+        self.get(lang, kind, id).map(|entry| entry.file.as_str())
+    }
+
+    pub fn ids(&self, lang: Language, kind: StringKind) -> impl Iterator<Item = i32> + '_ {
+        self.strings
+            .get(&lang)
+            .and_then(|kinds| kinds.get(&kind))
+            .into_iter()
+            .flat_map(|entries| entries.keys().copied())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    pub(crate) fn to_mod_str_array(&self) -> ModStringArray {
+        let mut mod_str_array = ModStringArray::default();
+        for (lang, kinds) in &self.strings {
+            let Some(data) = mod_str_array.get_mut(*lang) else {
+                continue;
+            };
+            let data = data.get_or_insert_with(ModStringArrayData::default);
+            for (kind, entries) in kinds {
+                let table = match kind {
+                    StringKind::Module => &mut data.module,
+                    StringKind::CstmItem => &mut data.customize,
+                };
+                table
+                    .get_or_insert_with(BTreeMap::new)
+                    .extend(entries.iter().map(|(id, entry)| (*id, entry.value.clone())));
+            }
+        }
+        mod_str_array
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Files(BTreeMap<&'static str, &'static str>);
+
+    impl TableSource for Files {
+        fn read(&self, table: Table) -> Option<Vec<u8>> {
+            self.read_path(table.path())
+        }
+
+        fn read_path(&self, path: &str) -> Option<Vec<u8>> {
+            self.0.get(path).map(|file| file.as_bytes().to_vec())
+        }
+    }
+
+    fn value(strings: &ModStrings, lang: Language, id: i32) -> Option<&str> {
+        strings
+            .get(lang, StringKind::Module, id)
+            .map(|entry| entry.value.as_str())
+    }
+
+    #[test]
+    fn split_beats_combined() {
+        let strings = ModStrings::load(&Files(BTreeMap::from([
+            (
+                "lang2/mod_str_array.toml",
+                "[module]\n1 = \"Default\"\n[en.module]\n1 = \"Combined\"\n2 = \"Combined only\"\n",
+            ),
+            (
+                "lang2/mod_str_array_en.toml",
+                "[module]\n1 = \"Split\"\n[customize]\n5 = \"Hair\"\n",
+            ),
+        ])));
+
+        assert_eq!(value(&strings, Language::En, 1), Some("Split"));
+        assert_eq!(value(&strings, Language::En, 2), Some("Combined only"));
+        assert_eq!(value(&strings, Language::Default, 1), Some("Default"));
+        assert_eq!(
+            strings.origin(Language::En, StringKind::Module, 1),
+            Some("lang2/mod_str_array_en.toml")
+        );
+        assert_eq!(
+            strings.origin(Language::En, StringKind::Module, 2),
+            Some("lang2/mod_str_array.toml")
+        );
+        assert_eq!(
+            strings.origin(Language::En, StringKind::CstmItem, 5),
+            Some("lang2/mod_str_array_en.toml")
+        );
+    }
+
+    #[test]
+    fn lang2_beats_lang() {
+        let strings = ModStrings::load(&Files(BTreeMap::from([
+            (
+                "lang/mod_str_array.toml",
+                "[fr.module]\n1 = \"Legacy\"\n2 = \"Legacy only\"\n",
+            ),
+            (
+                "lang/mod_str_array_en.toml",
+                "[module]\n1 = \"Legacy split\"\n",
+            ),
+            (
+                "lang2/mod_str_array.toml",
+                "[en.module]\n1 = \"Combined\"\n",
+            ),
+            ("lang2/mod_str_array_fr.toml", "[module]\n1 = \"Split\"\n"),
+        ])));
+
+        // Even a combined lang2 file beats a split lang one
+        assert_eq!(value(&strings, Language::En, 1), Some("Combined"));
+        assert_eq!(
+            strings.origin(Language::En, StringKind::Module, 1),
+            Some("lang2/mod_str_array.toml")
+        );
+        assert_eq!(value(&strings, Language::Fr, 1), Some("Split"));
+        assert_eq!(
+            strings.origin(Language::Fr, StringKind::Module, 1),
+            Some("lang2/mod_str_array_fr.toml")
+        );
+        assert_eq!(
+            strings.origin(Language::Fr, StringKind::Module, 2),
+            Some("lang/mod_str_array.toml")
+        );
+        assert_eq!(strings.origin(Language::Fr, StringKind::Module, 3), None);
+    }
+}
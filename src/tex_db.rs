@@ -0,0 +1,233 @@
+use crate::binary::{read_offset, read_string, read_u32};
+use crate::source::{FolderSource, TableSource};
+use crate::validate::{Location, Rule, Severity};
+use crate::ModuleDb;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Serialize, Clone, Default)]
+pub struct TexDb {
+    pub textures: BTreeMap<u32, String>,
+    #[serde(skip)]
+    names: BTreeMap<String, u32>,
+}
+
+// Relative to the rom folder, later files override earlier ones
+pub const TEX_DB_FILES: [&str; 2] = ["objset/tex_db.bin", "objset/mod_tex_db.bin"];
+
+impl TexDb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let count = read_u32(data, 0x00)? as usize;
+        let offset = read_offset(data, 0x04)?;
+
+        let mut textures = BTreeMap::new();
+        for i in 0..count {
+            let offset = offset + i * 0x08;
+            textures.insert(read_u32(data, offset)?, read_string(data, offset + 0x04)?);
+        }
+
+        let mut tex_db = Self {
+            textures,
+            ..Default::default()
+        };
+        tex_db.index();
+        Some(tex_db)
+    }
+
+    // The base game's tex_db.bin followed by the mod's mod_tex_db.bin
+    pub fn from_source<S: TableSource + ?Sized>(source: &S) -> Option<Self> {
+        let mut tex_db: Option<Self> = None;
+        for file in TEX_DB_FILES {
+            let Some(other) = source
+                .read_path(file)
+                .and_then(|data| Self::from_bytes(&data))
+            else {
+                continue;
+            };
+            match &mut tex_db {
+                Some(tex_db) => tex_db.merge(other),
+                None => tex_db = Some(other),
+            }
+        }
+        tex_db
+    }
+
+    // Merged over rom folders given lowest priority first
+    pub fn from_folders<P: AsRef<Path>>(folders: &[P]) -> Option<Self> {
+        let mut tex_db: Option<Self> = None;
+        for folder in folders {
+            let Some(other) = Self::from_source(&FolderSource::new(folder.as_ref())) else {
+                continue;
+            };
+            match &mut tex_db {
+                Some(tex_db) => tex_db.merge(other),
+                None => tex_db = Some(other),
+            }
+        }
+        tex_db
+    }
+
+    // Textures in other replace those with the same id
+    pub fn merge(&mut self, other: Self) {
+        self.textures.extend(other.textures);
+        self.index();
+    }
+
+    fn index(&mut self) {
+        self.names = self
+            .textures
+            .iter()
+            .map(|(id, name)| (name.to_uppercase(), *id))
+            .collect();
+    }
+
+    pub fn name(&self, id: u32) -> Option<&str> {
+        self.textures.get(&id).map(|name| name.as_str())
+    }
+
+    // Swaps are usually written as names, but plain ids work as well
+    pub fn id(&self, texture: &str) -> Option<u32> {
+        if let Some(id) = self.names.get(&texture.to_uppercase()) {
+            return Some(*id);
+        }
+        texture
+            .parse::<u32>()
+            .ok()
+            .filter(|id| self.textures.contains_key(id))
+    }
+}
+
+impl ModuleDb {
+    // Fills in the ids of both sides of every texture swap tex_db knows about
+    pub fn resolve_textures(&mut self, tex_db: &TexDb) {
+        for module in self.modules.values_mut() {
+            for item in &mut module.cos.items {
+                for swap in &mut item.textures {
+                    swap.org_id = tex_db.id(&swap.org);
+                    swap.chg_id = tex_db.id(&swap.chg);
+                }
+            }
+        }
+    }
+}
+
+// Texture swaps pointing at textures missing from the texture database
+pub struct TexDbCheck {
+    pub tex_db: TexDb,
+}
+
+impl TexDbCheck {
+    pub fn new(tex_db: TexDb) -> Self {
+        Self { tex_db }
+    }
+}
+
+impl Rule for TexDbCheck {
+    fn id(&self) -> &'static str {
+        "unknown-texture"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, module_db: &ModuleDb) -> Vec<(Location, String)> {
+        let mut findings = Vec::new();
+        for (id, module) in &module_db.modules {
+            for item in &module.cos.items {
+                let location = Location::CostumeItem {
+                    module: *id,
+                    item: item.id,
+                };
+                for swap in &item.textures {
+                    for texture in [&swap.org, &swap.chg] {
+                        if self.tex_db.id(texture).is_none() {
+                            findings.push((
+                                location.clone(),
+                                format!("Texture {texture} not in tex_db"),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::write::{put_string, put_u32};
+    use crate::source::Table;
+
+    fn tex_db_bin(textures: &[(u32, &str)]) -> Vec<u8> {
+        let mut data = vec![0; 0x08 + textures.len() * 0x08];
+        put_u32(&mut data, 0x00, textures.len() as u32);
+        put_u32(&mut data, 0x04, 0x08);
+        for (i, (id, name)) in textures.iter().enumerate() {
+            let offset = 0x08 + i * 0x08;
+            put_u32(&mut data, offset, *id);
+            put_string(&mut data, offset + 0x04, name);
+        }
+        data
+    }
+
+    // Serves files by their path relative to rom
+    struct Files(BTreeMap<&'static str, Vec<u8>>);
+
+    impl TableSource for Files {
+        fn read(&self, table: Table) -> Option<Vec<u8>> {
+            self.read_path(table.path())
+        }
+
+        fn read_path(&self, path: &str) -> Option<Vec<u8>> {
+            self.0.get(path).cloned()
+        }
+    }
+
+    #[test]
+    fn reads_textures() {
+        let data = tex_db_bin(&[(3001, "MIKITM001_TEX"), (3002, "MIKITM001_TEX_ALT")]);
+        let tex_db = TexDb::from_bytes(&data).unwrap();
+        assert_eq!(tex_db.textures.len(), 2);
+        assert_eq!(tex_db.name(3002), Some("MIKITM001_TEX_ALT"));
+        assert_eq!(tex_db.id("mikitm001_tex"), Some(3001));
+        assert_eq!(tex_db.id("3002"), Some(3002));
+        assert_eq!(tex_db.id("3003"), None);
+    }
+
+    #[test]
+    fn mod_tex_db_overrides_the_base() {
+        let files = Files(BTreeMap::from([
+            (
+                TEX_DB_FILES[0],
+                tex_db_bin(&[(3001, "BASE_TEX"), (3002, "BASE_TEX_ALT")]),
+            ),
+            (
+                TEX_DB_FILES[1],
+                tex_db_bin(&[(3002, "MOD_TEX_ALT"), (3003, "MOD_TEX")]),
+            ),
+        ]));
+        let tex_db = TexDb::from_source(&files).unwrap();
+        assert_eq!(tex_db.name(3001), Some("BASE_TEX"));
+        assert_eq!(tex_db.name(3002), Some("MOD_TEX_ALT"));
+        assert_eq!(tex_db.name(3003), Some("MOD_TEX"));
+        // The replaced name no longer resolves
+        assert_eq!(tex_db.id("BASE_TEX_ALT"), None);
+        assert_eq!(tex_db.id("mod_tex_alt"), Some(3002));
+
+        // Either file alone is enough
+        let files = Files(BTreeMap::from([(
+            TEX_DB_FILES[1],
+            tex_db_bin(&[(3003, "MOD_TEX")]),
+        )]));
+        assert_eq!(TexDb::from_source(&files).unwrap().textures.len(), 1);
+        assert!(TexDb::from_source(&Files(BTreeMap::new())).is_none());
+    }
+}
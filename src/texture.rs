@@ -0,0 +1,417 @@
+// Decoding of the TXP texture sets sprite files embed, everything ends up as RGBA8
+use crate::binary::{read_offset, read_u32};
+use crate::sprite::Image;
+
+const TEXTURE_SET_SIGNATURE: u32 = 0x03505854;
+const MIPMAP_SIGNATURE: u32 = 0x02505854;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    A8,
+    Rgb8,
+    Rgba8,
+    Rgb5,
+    Rgb5A1,
+    Rgba4,
+    Dxt1,
+    Dxt1a,
+    Dxt3,
+    Dxt5,
+    Ati1,
+    Ati2,
+    L8,
+    L8A8,
+}
+
+impl Format {
+    fn from_id(id: u32) -> Option<Self> {
+        Some(match id {
+            0 => Self::A8,
+            1 => Self::Rgb8,
+            2 => Self::Rgba8,
+            3 => Self::Rgb5,
+            4 => Self::Rgb5A1,
+            5 => Self::Rgba4,
+            6 => Self::Dxt1,
+            7 => Self::Dxt1a,
+            8 => Self::Dxt3,
+            9 => Self::Dxt5,
+            10 => Self::Ati1,
+            11 => Self::Ati2,
+            12 => Self::L8,
+            13 => Self::L8A8,
+            _ => return None,
+        })
+    }
+}
+
+struct Mipmap<'a> {
+    width: usize,
+    height: usize,
+    format: Format,
+    data: &'a [u8],
+}
+
+pub(crate) struct Texture<'a> {
+    mipmaps: Vec<Mipmap<'a>>,
+}
+
+pub(crate) fn read_texture_set(data: &[u8]) -> Option<Vec<Texture<'_>>> {
+    if read_u32(data, 0x00)? != TEXTURE_SET_SIGNATURE {
+        return None;
+    }
+    let count = read_u32(data, 0x04)? as usize;
+    (0..count)
+        .map(|i| read_texture(data.get(read_offset(data, 0x0C + i * 4)?..)?))
+        .collect()
+}
+
+fn read_texture(data: &[u8]) -> Option<Texture<'_>> {
+    let count = read_u32(data, 0x04)? as usize;
+    // Cube maps and arrays store every mipmap of the first layer first
+    let mipmap_count = (read_u32(data, 0x08)? & 0xFF) as usize;
+    let mipmaps = (0..count.min(mipmap_count))
+        .map(|i| read_mipmap(data.get(read_offset(data, 0x0C + i * 4)?..)?))
+        .collect::<Option<Vec<_>>>()?;
+    Some(Texture { mipmaps })
+}
+
+fn read_mipmap(data: &[u8]) -> Option<Mipmap<'_>> {
+    if read_u32(data, 0x00)? != MIPMAP_SIGNATURE {
+        return None;
+    }
+    let width = read_u32(data, 0x04)? as usize;
+    let height = read_u32(data, 0x08)? as usize;
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let size = read_offset(data, 0x14)?;
+    Some(Mipmap {
+        width,
+        height,
+        format: Format::from_id(read_u32(data, 0x0C)?)?,
+        data: data.get(0x18..0x18usize.checked_add(size)?)?,
+    })
+}
+
+impl Texture<'_> {
+    // The first mipmap, images are stored bottom row first like OpenGL expects
+    pub(crate) fn decode(&self) -> Option<Image> {
+        let mipmap = self.mipmaps.first()?;
+        // Sprites squeeze colour into two ATI2 mipmaps, luma and alpha at full
+        // size then chroma at half size
+        if mipmap.format == Format::Ati2 && self.mipmaps.len() == 2 {
+            return decode_ycbcr(mipmap, &self.mipmaps[1]);
+        }
+        decode(mipmap)
+    }
+}
+
+fn decode(mipmap: &Mipmap) -> Option<Image> {
+    match mipmap.format {
+        Format::A8 => decode_pixels(mipmap, 1, |p| [0, 0, 0, p[0]]),
+        Format::Rgb8 => decode_pixels(mipmap, 3, |p| [p[0], p[1], p[2], 0xFF]),
+        Format::Rgba8 => decode_pixels(mipmap, 4, |p| [p[0], p[1], p[2], p[3]]),
+        Format::Rgb5 => decode_pixels(mipmap, 2, |p| {
+            let [r, g, b] = rgb565(u16::from_le_bytes([p[0], p[1]]));
+            [r, g, b, 0xFF]
+        }),
+        Format::Rgb5A1 => decode_pixels(mipmap, 2, |p| {
+            let p = u16::from_le_bytes([p[0], p[1]]);
+            [
+                expand(p >> 10, 5),
+                expand(p >> 5, 5),
+                expand(p, 5),
+                if p & 0x8000 != 0 { 0xFF } else { 0 },
+            ]
+        }),
+        Format::Rgba4 => decode_pixels(mipmap, 2, |p| {
+            let p = u16::from_le_bytes([p[0], p[1]]);
+            [
+                expand(p >> 8, 4),
+                expand(p >> 4, 4),
+                expand(p, 4),
+                expand(p >> 12, 4),
+            ]
+        }),
+        Format::L8 => decode_pixels(mipmap, 1, |p| [p[0], p[0], p[0], 0xFF]),
+        Format::L8A8 => decode_pixels(mipmap, 2, |p| [p[0], p[0], p[0], p[1]]),
+        Format::Dxt1 => decode_blocks(mipmap, 8, |block| bc1(block, false)),
+        Format::Dxt1a => decode_blocks(mipmap, 8, |block| bc1(block, true)),
+        Format::Dxt3 => decode_blocks(mipmap, 16, bc2),
+        Format::Dxt5 => decode_blocks(mipmap, 16, bc3),
+        Format::Ati1 => decode_blocks(mipmap, 8, |block| bc4(block).map(|l| [l, l, l, 0xFF])),
+        Format::Ati2 => decode_blocks(mipmap, 16, |block| {
+            let (r, g) = bc5(block);
+            std::array::from_fn(|i| [r[i], g[i], 0, 0xFF])
+        }),
+    }
+}
+
+// BT.709 with the bias the game's shaders use
+fn decode_ycbcr(luma: &Mipmap, chroma: &Mipmap) -> Option<Image> {
+    let luma = decode(luma)?;
+    let chroma = decode(chroma)?;
+    let mut image = Image::new(luma.width, luma.height);
+    for y in 0..luma.height {
+        for x in 0..luma.width {
+            let [l, a, _, _] = luma.pixel(x, y)?;
+            let cx = (x * chroma.width / luma.width).min(chroma.width.checked_sub(1)?);
+            let cy = (y * chroma.height / luma.height).min(chroma.height.checked_sub(1)?);
+            let [cb, cr, _, _] = chroma.pixel(cx, cy)?;
+
+            let l = l as f32 / 255.0;
+            let cb = cb as f32 / 255.0 * 1.003922 - 0.503929;
+            let cr = cr as f32 / 255.0 * 1.003922 - 0.503929;
+            let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+            image.set_pixel(
+                x,
+                y,
+                [
+                    to_u8(l + 1.5748 * cr),
+                    to_u8(l - 0.1873 * cb - 0.4681 * cr),
+                    to_u8(l + 1.8556 * cb),
+                    a,
+                ],
+            );
+        }
+    }
+    Some(image)
+}
+
+fn decode_pixels(mipmap: &Mipmap, size: usize, pixel: impl Fn(&[u8]) -> [u8; 4]) -> Option<Image> {
+    let len = mipmap.width.checked_mul(mipmap.height)?.checked_mul(size)?;
+    let data = mipmap.data.get(..len)?;
+    let mut image = Image::new(mipmap.width, mipmap.height);
+    for (i, p) in data.chunks_exact(size).enumerate() {
+        image.set_pixel(i % mipmap.width, i / mipmap.width, pixel(p));
+    }
+    Some(image)
+}
+
+// 4x4 blocks, the last row and column are cut to the image size
+fn decode_blocks(
+    mipmap: &Mipmap,
+    size: usize,
+    block: impl Fn(&[u8]) -> [[u8; 4]; 16],
+) -> Option<Image> {
+    let blocks_x = mipmap.width.div_ceil(4);
+    let blocks_y = mipmap.height.div_ceil(4);
+    let len = blocks_x.checked_mul(blocks_y)?.checked_mul(size)?;
+    let data = mipmap.data.get(..len)?;
+    let mut image = Image::new(mipmap.width, mipmap.height);
+    for (i, data) in data.chunks_exact(size).enumerate() {
+        let pixels = block(data);
+        let (bx, by) = (i % blocks_x * 4, i / blocks_x * 4);
+        for (j, pixel) in pixels.into_iter().enumerate() {
+            image.set_pixel(bx + j % 4, by + j / 4, pixel);
+        }
+    }
+    Some(image)
+}
+
+fn expand(value: u16, bits: u32) -> u8 {
+    let max = (1u16 << bits) - 1;
+    ((value & max) as u32 * 255 / max as u32) as u8
+}
+
+fn rgb565(color: u16) -> [u8; 3] {
+    [
+        expand(color >> 11, 5),
+        expand(color >> 5, 6),
+        expand(color, 5),
+    ]
+}
+
+fn bc1(block: &[u8], alpha: bool) -> [[u8; 4]; 16] {
+    bc1_colors(block, Some(if alpha { 0 } else { 0xFF }))
+}
+
+// Colour half of BC1 to BC3, black is the alpha of the fourth colour in BC1's
+// three colour mode which BC2 and BC3 don't have
+fn bc1_colors(block: &[u8], black: Option<u8>) -> [[u8; 4]; 16] {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let [r0, g0, b0] = rgb565(c0).map(|c| c as u32);
+    let [r1, g1, b1] = rgb565(c1).map(|c| c as u32);
+    let mix = |a: u32, b: u32, wa: u32, wb: u32| ((a * wa + b * wb) / (wa + wb)) as u8;
+
+    let mut palette = [
+        [r0 as u8, g0 as u8, b0 as u8, 0xFF],
+        [r1 as u8, g1 as u8, b1 as u8, 0xFF],
+        [0; 4],
+        [0; 4],
+    ];
+    if c0 > c1 || black.is_none() {
+        palette[2] = [
+            mix(r0, r1, 2, 1),
+            mix(g0, g1, 2, 1),
+            mix(b0, b1, 2, 1),
+            0xFF,
+        ];
+        palette[3] = [
+            mix(r0, r1, 1, 2),
+            mix(g0, g1, 1, 2),
+            mix(b0, b1, 1, 2),
+            0xFF,
+        ];
+    } else {
+        palette[2] = [
+            mix(r0, r1, 1, 1),
+            mix(g0, g1, 1, 1),
+            mix(b0, b1, 1, 1),
+            0xFF,
+        ];
+        palette[3] = [0, 0, 0, black.unwrap_or_default()];
+    }
+
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    std::array::from_fn(|i| palette[(indices >> (i * 2)) as usize & 3])
+}
+
+fn bc2(block: &[u8]) -> [[u8; 4]; 16] {
+    let alpha = u64::from_le_bytes(block[..8].try_into().unwrap_or_default());
+    let mut pixels = bc1_colors(&block[8..], None);
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        pixel[3] = expand((alpha >> (i * 4)) as u16, 4);
+    }
+    pixels
+}
+
+fn bc3(block: &[u8]) -> [[u8; 4]; 16] {
+    let alpha = bc4(&block[..8]);
+    let mut pixels = bc1_colors(&block[8..], None);
+    for (pixel, alpha) in pixels.iter_mut().zip(alpha) {
+        pixel[3] = alpha;
+    }
+    pixels
+}
+
+// Single channel block, also the alpha half of BC3
+fn bc4(block: &[u8]) -> [u8; 16] {
+    let (a0, a1) = (block[0] as u32, block[1] as u32);
+    let mut palette = [a0 as u8, a1 as u8, 0, 0, 0, 0, 0, 0];
+    if a0 > a1 {
+        for i in 1..7 {
+            palette[i + 1] = ((a0 * (7 - i as u32) + a1 * i as u32) / 7) as u8;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i + 1] = ((a0 * (5 - i as u32) + a1 * i as u32) / 5) as u8;
+        }
+        palette[6] = 0;
+        palette[7] = 0xFF;
+    }
+
+    let mut bytes = [0; 8];
+    bytes[..6].copy_from_slice(&block[2..8]);
+    let indices = u64::from_le_bytes(bytes);
+    std::array::from_fn(|i| palette[(indices >> (i * 3)) as usize & 7])
+}
+
+fn bc5(block: &[u8]) -> ([u8; 16], [u8; 16]) {
+    (bc4(&block[..8]), bc4(&block[8..16]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::write::put_u32;
+
+    const RED: u16 = 0xF800;
+    const GREEN: u16 = 0x07E0;
+    const BLUE: u16 = 0x001F;
+    const WHITE: u16 = 0xFFFF;
+
+    fn bc1_block(c0: u16, c1: u16, indices: u32) -> Vec<u8> {
+        [c0.to_le_bytes(), c1.to_le_bytes()]
+            .concat()
+            .into_iter()
+            .chain(indices.to_le_bytes())
+            .collect()
+    }
+
+    fn mipmap_bin(width: u32, height: u32, format: u32, data: &[u8]) -> Vec<u8> {
+        let mut bin = vec![0; 0x18];
+        put_u32(&mut bin, 0x00, MIPMAP_SIGNATURE);
+        put_u32(&mut bin, 0x04, width);
+        put_u32(&mut bin, 0x08, height);
+        put_u32(&mut bin, 0x0C, format);
+        put_u32(&mut bin, 0x14, data.len() as u32);
+        bin.extend_from_slice(data);
+        bin
+    }
+
+    #[test]
+    fn bc1_four_colours() {
+        // Pixels 0 to 3 use palette entries 0 to 3
+        let pixels = bc1(&bc1_block(RED, BLUE, 0xE4), true);
+        assert_eq!(pixels[0], [0xFF, 0, 0, 0xFF]);
+        assert_eq!(pixels[1], [0, 0, 0xFF, 0xFF]);
+        assert_eq!(pixels[2], [170, 0, 85, 0xFF]);
+        assert_eq!(pixels[3], [85, 0, 170, 0xFF]);
+    }
+
+    #[test]
+    fn bc1_three_colours() {
+        let block = bc1_block(BLUE, RED, 0xE4);
+        let pixels = bc1(&block, false);
+        assert_eq!(pixels[0], [0, 0, 0xFF, 0xFF]);
+        assert_eq!(pixels[1], [0xFF, 0, 0, 0xFF]);
+        assert_eq!(pixels[2], [127, 0, 127, 0xFF]);
+        assert_eq!(pixels[3], [0, 0, 0, 0xFF]);
+        // Only DXT1a makes the fourth colour transparent
+        assert_eq!(bc1(&block, true)[3], [0, 0, 0, 0]);
+        // BC2 and BC3 always use four colours
+        assert_eq!(bc1_colors(&block, None)[3], [170, 0, 85, 0xFF]);
+    }
+
+    #[test]
+    fn bc4_six_values() {
+        // Pixels 0 to 7 use palette entries 0 to 7
+        let block = [0, 100, 0x88, 0xC6, 0xFA, 0, 0, 0];
+        let values = bc4(&block);
+        assert_eq!(values[..8], [0, 100, 20, 40, 60, 80, 0, 0xFF]);
+        assert_eq!(values[8..], [0; 8]);
+    }
+
+    #[test]
+    fn bc4_eight_values() {
+        let block = [140, 0, 0x88, 0xC6, 0xFA, 0, 0, 0];
+        let values = bc4(&block);
+        assert_eq!(values[..8], [140, 0, 120, 100, 80, 60, 40, 20]);
+    }
+
+    #[test]
+    fn partial_edge_blocks() {
+        // 5x5 takes 2x2 blocks, each of a single colour
+        let data = [RED, GREEN, BLUE, WHITE]
+            .into_iter()
+            .flat_map(|color| bc1_block(color, color, 0))
+            .collect::<Vec<_>>();
+        let bin = mipmap_bin(5, 5, 6, &data);
+        let image = decode(&read_mipmap(&bin).unwrap()).unwrap();
+        assert_eq!((image.width, image.height), (5, 5));
+        assert_eq!(image.data.len(), 5 * 5 * 4);
+        assert_eq!(image.pixel(3, 3), Some([0xFF, 0, 0, 0xFF]));
+        assert_eq!(image.pixel(4, 0), Some([0, 0xFF, 0, 0xFF]));
+        assert_eq!(image.pixel(0, 4), Some([0, 0, 0xFF, 0xFF]));
+        assert_eq!(image.pixel(4, 4), Some([0xFF; 4]));
+        assert_eq!(image.pixel(5, 4), None);
+
+        // One block short
+        let bin = mipmap_bin(5, 5, 6, &data[..24]);
+        assert!(decode(&read_mipmap(&bin).unwrap()).is_none());
+    }
+
+    #[test]
+    fn bad_sizes_are_none() {
+        assert!(read_mipmap(&mipmap_bin(0, 4, 2, &[0; 16])).is_none());
+        assert!(read_mipmap(&mipmap_bin(4, 0, 2, &[0; 16])).is_none());
+
+        for format in [2, 6] {
+            let bin = mipmap_bin(u32::MAX, u32::MAX, format, &[0; 64]);
+            assert!(decode(&read_mipmap(&bin).unwrap()).is_none());
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::source::TableSource;
+use crate::strings::{ModStrings, StringKind};
+use crate::{Language, ModuleDb};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Clone, Default, PartialEq)]
+pub struct LanguageCoverage {
+    pub missing_modules: Vec<i32>,
+    pub missing_cstm_items: Vec<i32>,
+    // Ids in mod_str_array.toml without a matching module or customize item
+    pub orphan_modules: Vec<i32>,
+    pub orphan_cstm_items: Vec<i32>,
+    pub translated: usize,
+    pub total: usize,
+    // 0 to 100, a database with nothing to translate counts as fully covered
+    pub coverage: f64,
+}
+
+#[derive(Serialize, Clone, Default, PartialEq)]
+pub struct TranslationReport {
+    pub languages: BTreeMap<Language, LanguageCoverage>,
+}
+
+fn orphans<T>(ids: impl Iterator<Item = i32>, known: &BTreeMap<i32, T>) -> Vec<i32> {
+    ids.filter(|id| !known.contains_key(id)).collect()
+}
+
+impl ModuleDb {
+    // Orphans are looked up in the string files of source, the names in the
+    // database only exist for known ids
+    pub fn translation_report<S: TableSource + ?Sized>(&self, source: &S) -> TranslationReport {
+        let strings = ModStrings::load(source);
+
+        let mut languages = BTreeMap::new();
+        for lang in Language::TRANSLATED {
+            let missing_modules = self
+                .modules
+                .iter()
+                .filter(|(_, module)| module.name.get(lang).is_none())
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>();
+            let missing_cstm_items = self
+                .cstm_items
+                .iter()
+                .filter(|(_, item)| item.name.get(lang).is_none())
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>();
+
+            let orphan_modules = orphans(strings.ids(lang, StringKind::Module), &self.modules);
+            let orphan_cstm_items =
+                orphans(strings.ids(lang, StringKind::CstmItem), &self.cstm_items);
+
+            let total = self.modules.len() + self.cstm_items.len();
+            let translated = total - missing_modules.len() - missing_cstm_items.len();
+            let coverage = if total == 0 {
+                100.0
+            } else {
+                translated as f64 * 100.0 / total as f64
+            };
+
+            languages.insert(
+                lang,
+                LanguageCoverage {
+                    missing_modules,
+                    missing_cstm_items,
+                    orphan_modules,
+                    orphan_cstm_items,
+                    translated,
+                    total,
+                    coverage,
+                },
+            );
+        }
+
+        TranslationReport { languages }
+    }
+}
@@ -0,0 +1,483 @@
+use crate::{ItemSub, Language, ModuleDb};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl ToString for Severity {
+    fn to_string(&self) -> String {
+        String::from(match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Location {
+    Module { id: i32 },
+    CostumeItem { module: i32, item: i32 },
+    CstmItem { id: i32 },
+}
+
+impl ToString for Location {
+    fn to_string(&self) -> String {
+        match self {
+            Self::Module { id } => format!("module.{id}"),
+            Self::CostumeItem { module, item } => format!("module.{module} item.{item}"),
+            Self::CstmItem { id } => format!("cstm_item.{id}"),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Finding {
+    pub rule: String,
+    pub severity: Severity,
+    pub location: Location,
+    pub message: String,
+}
+
+pub trait Rule {
+    // Stable name used to configure the rule
+    fn id(&self) -> &'static str;
+    fn severity(&self) -> Severity;
+    fn check(&self, module_db: &ModuleDb) -> Vec<(Location, String)>;
+}
+
+// Costumes and costume items the loader couldn't find in chritm_prop
+pub struct MissingCostume;
+
+impl Rule for MissingCostume {
+    fn id(&self) -> &'static str {
+        "missing-costume"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, module_db: &ModuleDb) -> Vec<(Location, String)> {
+        let mut findings = Vec::new();
+        for (id, module) in &module_db.modules {
+            if module.cos.missing {
+                findings.push((
+                    Location::Module { id: *id },
+                    format!("Costume {} not found in chritm_prop", module.cos.id),
+                ));
+            }
+            for item in &module.cos.missing_items {
+                findings.push((
+                    Location::CostumeItem {
+                        module: *id,
+                        item: *item,
+                    },
+                    format!(
+                        "Costume item {item} of costume {} not found in chritm_prop",
+                        module.cos.id
+                    ),
+                ));
+            }
+        }
+        findings
+    }
+}
+
+pub struct EmptyObjset;
+
+impl Rule for EmptyObjset {
+    fn id(&self) -> &'static str {
+        "empty-objset"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, module_db: &ModuleDb) -> Vec<(Location, String)> {
+        let mut findings = Vec::new();
+        for (id, module) in &module_db.modules {
+            for item in module.cos.resolved_items() {
+                if item.objset.is_empty() {
+                    findings.push((
+                        Location::CostumeItem {
+                            module: *id,
+                            item: item.id,
+                        },
+                        format!("Costume item {} has no objset", item.id),
+                    ));
+                }
+            }
+        }
+        findings
+    }
+}
+
+// Items can be bound to base game modules, so this is only a warning
+pub struct MissingBindModule;
+
+impl Rule for MissingBindModule {
+    fn id(&self) -> &'static str {
+        "missing-bind-module"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, module_db: &ModuleDb) -> Vec<(Location, String)> {
+        module_db
+            .cstm_items
+            .iter()
+            .filter_map(|(id, item)| {
+                // Negative ids mean the item isn't bound to a module
+                let bind_module = item.bind_module.filter(|module| *module >= 0)?;
+                if module_db.modules.contains_key(&bind_module) {
+                    return None;
+                }
+                Some((
+                    Location::CstmItem { id: *id },
+                    format!("Bound to missing module {bind_module}"),
+                ))
+            })
+            .collect()
+    }
+}
+
+pub struct MissingDefaultName;
+
+impl Rule for MissingDefaultName {
+    fn id(&self) -> &'static str {
+        "missing-default-name"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, module_db: &ModuleDb) -> Vec<(Location, String)> {
+        let modules = module_db
+            .modules
+            .iter()
+            .filter(|(_, module)| module.name.get(Language::Default).is_none())
+            .map(|(id, _)| Location::Module { id: *id });
+        let cstm_items = module_db
+            .cstm_items
+            .iter()
+            .filter(|(_, item)| item.name.get(Language::Default).is_none())
+            .map(|(id, _)| Location::CstmItem { id: *id });
+
+        modules
+            .chain(cstm_items)
+            .map(|location| (location, String::from("No name in mod_str_array.toml")))
+            .collect()
+    }
+}
+
+pub struct DuplicateItemSub;
+
+impl Rule for DuplicateItemSub {
+    fn id(&self) -> &'static str {
+        "duplicate-item-sub"
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, module_db: &ModuleDb) -> Vec<(Location, String)> {
+        let mut findings = Vec::new();
+        for (id, module) in &module_db.modules {
+            let mut subs: BTreeMap<ItemSub, Vec<i32>> = BTreeMap::new();
+            for item in module.cos.resolved_items() {
+                subs.entry(item.sub.clone()).or_default().push(item.id);
+            }
+            for (sub, items) in subs {
+                if items.len() < 2 {
+                    continue;
+                }
+                let items = items
+                    .iter()
+                    .map(|item| item.to_string())
+                    .collect::<Vec<_>>();
+                findings.push((
+                    Location::Module { id: *id },
+                    format!("Items {} share slot {}", items.join(", "), sub.to_string()),
+                ));
+            }
+        }
+        findings
+    }
+}
+
+// Every rule shipped with the crate
+pub fn default_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(MissingCostume),
+        Box::new(EmptyObjset),
+        Box::new(MissingBindModule),
+        Box::new(MissingDefaultName),
+        Box::new(DuplicateItemSub),
+    ]
+}
+
+pub struct Validator {
+    pub rules: Vec<Box<dyn Rule>>,
+    // Overrides keyed by rule id, None turns the rule off
+    pub config: BTreeMap<String, Option<Severity>>,
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Self {
+            rules: default_rules(),
+            config: BTreeMap::new(),
+        }
+    }
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Without any rules, for picking them one by one
+    pub fn empty() -> Self {
+        Self {
+            rules: Vec::new(),
+            config: BTreeMap::new(),
+        }
+    }
+
+    pub fn with<R: Rule + 'static>(mut self, rule: R) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    pub fn severity(mut self, rule: &str, severity: Severity) -> Self {
+        self.config.insert(rule.to_string(), Some(severity));
+        self
+    }
+
+    pub fn disable(mut self, rule: &str) -> Self {
+        self.config.insert(rule.to_string(), None);
+        self
+    }
+
+    pub fn validate(&self, module_db: &ModuleDb) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for rule in &self.rules {
+            let severity = match self.config.get(rule.id()) {
+                Some(Some(severity)) => *severity,
+                Some(None) => continue,
+                None => rule.severity(),
+            };
+            findings.extend(
+                rule.check(module_db)
+                    .into_iter()
+                    .map(|(location, message)| Finding {
+                        rule: rule.id().to_string(),
+                        severity,
+                        location,
+                        message,
+                    }),
+            );
+        }
+        findings
+    }
+}
+
+impl ModuleDb {
+    // Runs every default rule, use a Validator to configure them
+    pub fn validate(&self) -> Vec<Finding> {
+        Validator::new().validate(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build, Chara, ItemPart};
+
+    fn locations<R: Rule>(rule: R, module_db: &ModuleDb) -> Vec<Location> {
+        rule.check(module_db)
+            .into_iter()
+            .map(|(location, _)| location)
+            .collect()
+    }
+
+    #[test]
+    fn missing_costume() {
+        let mut missing = build::module(Chara::Miku, 3, Vec::new());
+        missing.cos.missing = true;
+        let mut partial = build::module(
+            Chara::Miku,
+            4,
+            vec![
+                build::item(1, ItemSub::Kami, &["MIKITM001"]),
+                build::item(2, ItemSub::Te, &[]),
+            ],
+        );
+        partial.cos.missing_items.push(2);
+        let module_db = build::module_db(
+            [
+                (1, missing),
+                (2, partial),
+                (3, build::module(Chara::Miku, 0, Vec::new())),
+            ],
+            [],
+        );
+
+        assert_eq!(
+            locations(MissingCostume, &module_db),
+            [
+                Location::Module { id: 1 },
+                Location::CostumeItem { module: 2, item: 2 }
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_objset() {
+        // The placeholder of a missing item is MissingCostume's to report
+        let mut module = build::module(
+            Chara::Miku,
+            0,
+            vec![
+                build::item(1, ItemSub::Kami, &["MIKITM001"]),
+                build::item(2, ItemSub::Outer, &[]),
+                build::item(3, ItemSub::Te, &[]),
+            ],
+        );
+        module.cos.missing_items.push(3);
+        let module_db = build::module_db([(1, module)], []);
+
+        assert_eq!(
+            locations(EmptyObjset, &module_db),
+            [Location::CostumeItem { module: 1, item: 2 }]
+        );
+    }
+
+    #[test]
+    fn missing_bind_module() {
+        let module_db = build::module_db(
+            [(1, build::module(Chara::Miku, 0, Vec::new()))],
+            [
+                (
+                    10,
+                    build::cstm_item(Chara::Miku, ItemPart::Kami, 1, Some(1)),
+                ),
+                (
+                    11,
+                    build::cstm_item(Chara::Miku, ItemPart::Kami, 2, Some(2)),
+                ),
+                (
+                    12,
+                    build::cstm_item(Chara::Miku, ItemPart::Kami, 3, Some(-1)),
+                ),
+                (13, build::cstm_item(Chara::Miku, ItemPart::Kami, 4, None)),
+            ],
+        );
+
+        assert_eq!(
+            locations(MissingBindModule, &module_db),
+            [Location::CstmItem { id: 11 }]
+        );
+    }
+
+    #[test]
+    fn missing_default_name() {
+        let mut named = build::module(Chara::Miku, 0, Vec::new());
+        named
+            .name
+            .set(Language::Default, Some(String::from("Miku")));
+        // Only a japanese name from the game tables
+        let mut japanese = build::module(Chara::Miku, 1, Vec::new());
+        japanese.name.set(Language::Jp, Some(String::from("ミク")));
+        let mut item = build::cstm_item(Chara::Miku, ItemPart::Kami, 1, None);
+        item.name.set(Language::En, Some(String::from("Hair")));
+        let module_db = build::module_db([(1, named), (2, japanese)], [(10, item)]);
+
+        assert_eq!(
+            locations(MissingDefaultName, &module_db),
+            [Location::Module { id: 2 }, Location::CstmItem { id: 10 }]
+        );
+    }
+
+    #[test]
+    fn duplicate_item_sub() {
+        let mut module = build::module(
+            Chara::Miku,
+            0,
+            vec![
+                build::item(1, ItemSub::Kami, &["MIKITM001"]),
+                build::item(2, ItemSub::Kami, &["MIKITM002"]),
+                build::item(3, ItemSub::Outer, &["MIKITM003"]),
+                build::item(4, ItemSub::Outer, &[]),
+            ],
+        );
+        // Item 4 is a placeholder and has no slot of its own
+        module.cos.missing_items.push(4);
+        let module_db = build::module_db([(1, module)], []);
+
+        let findings = DuplicateItemSub.check(&module_db);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].0, Location::Module { id: 1 });
+        assert_eq!(findings[0].1, "Items 1, 2 share slot Hair (Kami)");
+    }
+
+    #[test]
+    fn config_overrides_rules() {
+        let mut module = build::module(Chara::Miku, 0, Vec::new());
+        module.cos.missing = true;
+        let module_db = build::module_db(
+            [(1, module)],
+            [(
+                10,
+                build::cstm_item(Chara::Miku, ItemPart::Kami, 1, Some(2)),
+            )],
+        );
+
+        let rules = |findings: Vec<Finding>| {
+            findings
+                .into_iter()
+                .map(|finding| (finding.rule, finding.severity))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            rules(module_db.validate()),
+            [
+                (String::from("missing-costume"), Severity::Error),
+                (String::from("missing-bind-module"), Severity::Warning),
+                (String::from("missing-default-name"), Severity::Warning),
+                (String::from("missing-default-name"), Severity::Warning),
+            ]
+        );
+
+        let validator = Validator::new()
+            .disable("missing-default-name")
+            .severity("missing-costume", Severity::Warning)
+            .severity("missing-bind-module", Severity::Error);
+        assert_eq!(
+            rules(validator.validate(&module_db)),
+            [
+                (String::from("missing-costume"), Severity::Warning),
+                (String::from("missing-bind-module"), Severity::Error),
+            ]
+        );
+
+        let validator = Validator::empty().with(MissingBindModule);
+        assert_eq!(
+            rules(validator.validate(&module_db)),
+            [(String::from("missing-bind-module"), Severity::Warning)]
+        );
+    }
+}
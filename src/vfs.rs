@@ -0,0 +1,256 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize)]
+struct LoaderConfig {
+    mods: Option<String>,
+    priority: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct ModConfig {
+    enabled: Option<bool>,
+    include: Option<Vec<String>>,
+}
+
+#[derive(Clone)]
+pub struct VfsMod {
+    pub name: String,
+    pub path: PathBuf,
+    // Folders from the mods include list, each may contain a rom folder
+    pub roots: Vec<PathBuf>,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct VfsFile {
+    // None when served by the base game
+    pub mod_name: Option<String>,
+    pub path: PathBuf,
+}
+
+// Mods are kept highest priority first, matching the order of DML's priority list
+#[derive(Clone, Default)]
+pub struct Vfs {
+    pub base: Option<PathBuf>,
+    pub mods: Vec<VfsMod>,
+}
+
+fn read_config(path: &Path) -> Option<ModConfig> {
+    let contents = std::fs::read_to_string(path.join("config.toml")).ok()?;
+    toml::from_str(&contents).ok()
+}
+
+fn include_roots(path: &Path, include: Option<Vec<String>>) -> Vec<PathBuf> {
+    include
+        .unwrap_or(vec![String::from(".")])
+        .iter()
+        .map(|include| path.join(include))
+        .collect()
+}
+
+// The include roots of the mod at path even when it's disabled, or path itself
+// when there's no config.toml to read them from
+pub fn mod_roots<P: AsRef<Path>>(path: P) -> Vec<PathBuf> {
+    let path = path.as_ref();
+    match read_config(path) {
+        Some(config) => include_roots(path, config.include),
+        None => vec![path.to_path_buf()],
+    }
+}
+
+impl VfsMod {
+    pub fn from_folder<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let path = path.as_ref();
+        let config = read_config(path)?;
+        if !config.enabled.unwrap_or(true) {
+            return None;
+        }
+
+        Some(Self {
+            name: path.file_name()?.to_string_lossy().to_string(),
+            path: path.to_path_buf(),
+            roots: include_roots(path, config.include),
+        })
+    }
+
+    pub fn find(&self, path: &str) -> Option<PathBuf> {
+        self.roots.iter().find_map(|root| find_file(root, path))
+    }
+
+    // Every logical path this mod provides
+    pub fn files(&self) -> Vec<String> {
+        let mut files = Vec::new();
+        for root in &self.roots {
+            walk(root, root, &mut files);
+        }
+        files.sort();
+        files.dedup();
+        files
+    }
+}
+
+fn walk(root: &Path, dir: &Path, files: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            walk(root, &path, files);
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            if relative == Path::new("config.toml") {
+                continue;
+            }
+            files.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+    }
+}
+
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches('/').to_string()
+}
+
+// The game runs on Windows, so rom/Objset/X.farc and rom/objset/x.farc are the same file
+fn find_file(root: &Path, path: &str) -> Option<PathBuf> {
+    let exact = root.join(path);
+    if exact.is_file() {
+        return Some(exact);
+    }
+
+    let mut found = root.to_path_buf();
+    for component in path.split('/').filter(|component| !component.is_empty()) {
+        found = std::fs::read_dir(&found)
+            .ok()?
+            .flatten()
+            .find(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .eq_ignore_ascii_case(component)
+            })?
+            .path();
+    }
+    found.is_file().then_some(found)
+}
+
+// DML's priority list when mods is the mods folder named in the config.toml next to it
+pub fn loader_priority<P: AsRef<Path>>(mods: P) -> Vec<String> {
+    let priority = || -> Option<Vec<String>> {
+        let mods = mods.as_ref().canonicalize().ok()?;
+        let game = mods.parent()?;
+        let contents = std::fs::read_to_string(game.join("config.toml")).ok()?;
+        let config: LoaderConfig = toml::from_str(&contents).ok()?;
+        let configured = game.join(config.mods.as_deref().unwrap_or("mods"));
+        if configured.canonicalize().ok()? != mods {
+            return None;
+        }
+        config.priority
+    };
+    priority().unwrap_or_default()
+}
+
+impl Vfs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Reads DML's own config.toml for the mods folder and priority list
+    pub fn from_game_folder<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path.join("config.toml")).ok()?;
+        let config: LoaderConfig = toml::from_str(&contents).ok()?;
+        let mods = path.join(config.mods.as_deref().unwrap_or("mods"));
+
+        let mut vfs = Self::from_mods_folder(mods, &config.priority.unwrap_or_default())?;
+        vfs.base = Some(path.to_path_buf());
+        Some(vfs)
+    }
+
+    // Mods missing from the priority list go after those in it, in name order
+    pub fn from_mods_folder<P: AsRef<Path>>(path: P, priority: &[String]) -> Option<Self> {
+        let mut mods = std::fs::read_dir(path)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .filter_map(VfsMod::from_folder)
+            .collect::<Vec<_>>();
+
+        mods.sort_by(|a, b| {
+            let a_priority = priority.iter().position(|name| name == &a.name);
+            let b_priority = priority.iter().position(|name| name == &b.name);
+            match (a_priority, b_priority) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => a.name.cmp(&b.name),
+            }
+        });
+
+        Some(Self { base: None, mods })
+    }
+
+    pub fn with_base<P: Into<PathBuf>>(mut self, base: P) -> Self {
+        self.base = Some(base.into());
+        self
+    }
+
+    // The file the game would load for a path such as rom/objset/mikitm123.farc
+    pub fn resolve(&self, path: &str) -> Option<VfsFile> {
+        self.providers(path).into_iter().next()
+    }
+
+    // Everything providing a path, the first entry shadows all the others
+    pub fn providers(&self, path: &str) -> Vec<VfsFile> {
+        let path = normalize(path);
+        let mut files = self
+            .mods
+            .iter()
+            .filter_map(|vfs_mod| {
+                Some(VfsFile {
+                    mod_name: Some(vfs_mod.name.clone()),
+                    path: vfs_mod.find(&path)?,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(base) = self.base.as_ref().and_then(|base| find_file(base, &path)) {
+            files.push(VfsFile {
+                mod_name: None,
+                path: base,
+            });
+        }
+
+        files
+    }
+
+    // Logical paths provided by more than one mod, with mod names in priority order.
+    // Paths are lowercased as the game doesn't care about case
+    pub fn conflicts(&self) -> BTreeMap<String, Vec<String>> {
+        let mut providers: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for vfs_mod in &self.mods {
+            let mut files = vfs_mod
+                .files()
+                .into_iter()
+                .map(|file| file.to_ascii_lowercase())
+                .collect::<Vec<_>>();
+            files.sort();
+            files.dedup();
+            for file in files {
+                // DML merges mod_ prefixed tables instead of replacing them
+                let name = file.rsplit('/').next().unwrap_or(&file);
+                if name.starts_with("mod_") {
+                    continue;
+                }
+                providers
+                    .entry(file)
+                    .or_default()
+                    .push(vfs_mod.name.clone());
+            }
+        }
+
+        providers.retain(|_, mods| mods.len() > 1);
+        providers
+    }
+}
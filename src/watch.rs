@@ -0,0 +1,210 @@
+use crate::source::input_files;
+use crate::vfs::VfsMod;
+use crate::ModuleDb;
+use notify::Watcher as _;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Entry {
+    Module(i32),
+    CstmItem(i32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Change {
+    Added(Entry),
+    Removed(Entry),
+    Modified(Entry),
+}
+
+#[derive(Clone, Debug)]
+pub struct WatchEvent {
+    pub root: PathBuf,
+    pub changes: Vec<Change>,
+}
+
+#[derive(Default)]
+struct State {
+    roots: BTreeMap<PathBuf, Option<ModuleDb>>,
+    priority: Vec<PathBuf>,
+    subscribers: Vec<Sender<WatchEvent>>,
+}
+
+pub struct Watcher {
+    state: Arc<Mutex<State>>,
+    _watcher: notify::RecommendedWatcher,
+}
+
+fn changes<T: PartialEq>(
+    old: Option<&BTreeMap<i32, T>>,
+    new: Option<&BTreeMap<i32, T>>,
+    entry: fn(i32) -> Entry,
+) -> Vec<Change> {
+    let empty = BTreeMap::new();
+    let old = old.unwrap_or(&empty);
+    let new = new.unwrap_or(&empty);
+
+    let mut changes = Vec::new();
+    for (id, value) in new {
+        match old.get(id) {
+            Some(old) if old == value => {}
+            Some(_) => changes.push(Change::Modified(entry(*id))),
+            None => changes.push(Change::Added(entry(*id))),
+        }
+    }
+    for id in old.keys() {
+        if !new.contains_key(id) {
+            changes.push(Change::Removed(entry(*id)));
+        }
+    }
+    changes
+}
+
+pub fn module_db_changes(old: Option<&ModuleDb>, new: Option<&ModuleDb>) -> Vec<Change> {
+    let mut all = changes(
+        old.map(|db| &db.modules),
+        new.map(|db| &db.modules),
+        Entry::Module,
+    );
+    all.extend(changes(
+        old.map(|db| &db.cstm_items),
+        new.map(|db| &db.cstm_items),
+        Entry::CstmItem,
+    ));
+    all.sort();
+    all
+}
+
+// Roots are rom folders, a config.toml next to them can disable the mod
+fn load(root: &Path) -> Option<ModuleDb> {
+    if let Some(mod_folder) = root.parent() {
+        if mod_folder.join("config.toml").exists() && VfsMod::from_folder(mod_folder).is_none() {
+            return None;
+        }
+    }
+    ModuleDb::from_folder(root)
+}
+
+// Deleted files can't be canonicalized, their folder still can
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize()
+        .ok()
+        .or_else(|| Some(path.parent()?.canonicalize().ok()?.join(path.file_name()?)))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+// Events may come with symlinks resolved or in another case than the roots
+fn is_input(root: &Path, path: &Path) -> bool {
+    let root = canonical(root);
+    let path = canonical(path);
+    let relative = |base: &Path| {
+        let relative = path.strip_prefix(base).ok()?;
+        Some(relative.to_string_lossy().replace('\\', "/"))
+    };
+
+    if root
+        .parent()
+        .and_then(relative)
+        .is_some_and(|file| file.eq_ignore_ascii_case("config.toml"))
+    {
+        return true;
+    }
+    let Some(file) = relative(&root) else {
+        return false;
+    };
+    input_files()
+        .iter()
+        .any(|input| input.eq_ignore_ascii_case(&file))
+}
+
+impl State {
+    fn reload(&mut self, paths: &[PathBuf]) {
+        let roots = self
+            .roots
+            .keys()
+            .filter(|root| paths.iter().any(|path| is_input(root, path)))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for root in roots {
+            let module_db = load(&root);
+            let old = self.roots.insert(root.clone(), module_db);
+            let changes = module_db_changes(old.flatten().as_ref(), self.roots[&root].as_ref());
+            if changes.is_empty() {
+                continue;
+            }
+
+            let event = WatchEvent { root, changes };
+            self.subscribers
+                .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+}
+
+impl Watcher {
+    pub fn new<P: AsRef<Path>>(roots: &[P]) -> Option<Self> {
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let handler_state = state.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                if event.kind.is_access() {
+                    return;
+                }
+                if let Ok(mut state) = handler_state.lock() {
+                    state.reload(&event.paths);
+                }
+            })
+            .ok()?;
+
+        let mut guard = state.lock().ok()?;
+        for root in roots {
+            let root = root.as_ref().to_path_buf();
+            // Watch the whole mod so config.toml and recreated folders are seen
+            let folder = root.parent().unwrap_or(&root);
+            watcher
+                .watch(folder, notify::RecursiveMode::Recursive)
+                .ok()?;
+            guard.roots.insert(root.clone(), load(&root));
+            guard.priority.push(root);
+        }
+        drop(guard);
+
+        Some(Self {
+            state,
+            _watcher: watcher,
+        })
+    }
+
+    pub fn subscribe(&self) -> Receiver<WatchEvent> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        if let Ok(mut state) = self.state.lock() {
+            state.subscribers.push(sender);
+        }
+        receiver
+    }
+
+    pub fn get<P: AsRef<Path>>(&self, root: P) -> Option<ModuleDb> {
+        self.state.lock().ok()?.roots.get(root.as_ref())?.clone()
+    }
+
+    // Roots are given highest priority first, like BatchLoad::load_merged
+    pub fn module_db(&self) -> Option<ModuleDb> {
+        let state = self.state.lock().ok()?;
+        let mut merged: Option<ModuleDb> = None;
+        let roots = state.priority.iter().rev();
+        for module_db in roots.filter_map(|root| state.roots.get(root)?.as_ref()) {
+            match &mut merged {
+                Some(merged) => merged.merge(module_db.clone()),
+                None => merged = Some(module_db.clone()),
+            }
+        }
+        merged
+    }
+}
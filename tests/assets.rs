@@ -0,0 +1,79 @@
+use module_db::assets::{unused_assets, AssetCheck};
+use module_db::batch::BatchLoad;
+use module_db::validate::{Location, Rule};
+use std::path::Path;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/assets");
+
+fn fixture(name: &str) -> std::path::PathBuf {
+    Path::new(FIXTURES).join(name)
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).unwrap().len()
+}
+
+#[test]
+fn packed_archives_are_used() {
+    let root = fixture("packed_mod");
+    let unused = unused_assets(&root);
+
+    // mod_objs.farc only appears in obj_db as the archive of MIKITM001
+    let objsets = unused
+        .objsets
+        .iter()
+        .map(|objset| objset.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(objsets, ["stray"]);
+
+    assert_eq!(unused.items.len(), 1);
+    assert_eq!(unused.items[0].id, 2);
+    assert_eq!(
+        unused.items[0].size,
+        file_size(&root.join("rom/objset/mikitm002.farc"))
+    );
+
+    let check = AssetCheck::new(&root);
+    assert_eq!(
+        check.find_objset("MIKITM001"),
+        Some(root.join("rom/objset/mod_objs.farc"))
+    );
+}
+
+#[test]
+fn include_roots_are_searched() {
+    let root = fixture("include_mod");
+    let unused = unused_assets(&root);
+
+    // Items come from base, the customize items using them and most farcs from extra
+    let objsets = unused
+        .objsets
+        .iter()
+        .map(|objset| objset.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(objsets, ["stray"]);
+    assert_eq!(unused.items.len(), 1);
+    assert_eq!(unused.items[0].id, 3);
+    assert_eq!(
+        unused.items[0].size,
+        file_size(&root.join("extra/rom/objset/mikitm003.farc"))
+    );
+
+    let check = AssetCheck::new(&root);
+    assert_eq!(
+        check.find_objset("MIKITM001"),
+        Some(root.join("extra/rom/objset/mikitm001.farc"))
+    );
+    assert_eq!(
+        check.find_objset("mikitm002"),
+        Some(root.join("base/rom/objset/mikitm002.farc"))
+    );
+
+    let module_db = BatchLoad::new()
+        .load_merged(&[root.join("base/rom"), root.join("extra/rom")])
+        .unwrap();
+    let findings = check.check(&module_db);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].0, Location::CstmItem { id: 21 });
+    assert!(findings[0].1.contains("MIKITM004"));
+}
@@ -0,0 +1,53 @@
+use module_db::batch::BatchLoad;
+use module_db::{Language, ModuleDb};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const MODS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/vfs/game/mods");
+
+fn rom(name: &str) -> PathBuf {
+    Path::new(MODS).join(name).join("rom")
+}
+
+fn name(module_db: &ModuleDb, id: i32) -> Option<&str> {
+    module_db.modules[&id].name.get(Language::En)
+}
+
+#[test]
+fn results_keep_the_root_order() {
+    let done = Mutex::new(Vec::new());
+    let roots = [rom("zeta"), rom("alpha"), rom("beta")];
+    let results = BatchLoad::new()
+        .threads(3)
+        .progress(|_, done_count, total| done.lock().unwrap().push((done_count, total)))
+        .load(&roots);
+
+    // zeta only ships an objset, so there's nothing to parse
+    assert!(results[0].is_none());
+    assert_eq!(name(results[1].as_ref().unwrap(), 1), Some("Alpha"));
+    assert_eq!(name(results[2].as_ref().unwrap(), 1), Some("Beta"));
+
+    let mut done = done.into_inner().unwrap();
+    done.sort();
+    assert_eq!(done, [(1, 3), (2, 3), (3, 3)]);
+}
+
+#[test]
+fn first_root_wins() {
+    let batch = BatchLoad::new();
+
+    // Whole modules are replaced, alpha's module 3 hides beta's name for it
+    let merged = batch.load_merged(&[rom("alpha"), rom("beta")]).unwrap();
+    assert_eq!(name(&merged, 1), Some("Alpha"));
+    assert_eq!(name(&merged, 3), None);
+
+    let merged = batch.load_merged(&[rom("beta"), rom("alpha")]).unwrap();
+    assert_eq!(name(&merged, 1), Some("Beta"));
+    assert_eq!(name(&merged, 3), Some("Beta 3"));
+
+    // zeta is first in the game's priority list but has no tables, then alpha
+    let merged = ModuleDb::from_mods_folder(MODS).unwrap();
+    assert_eq!(name(&merged, 1), Some("Alpha"));
+
+    assert!(batch.load_merged(&[rom("zeta")]).is_none());
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "server")]
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt;
+use module_db::server::{router, ServerState};
+use serde_json::Value;
+use tower::ServiceExt;
+
+const MODS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/mods");
+
+fn app() -> Router {
+    router(ServerState::from_mods_folder(MODS).expect("fixture mods should load"))
+}
+
+async fn send(app: Router, method: &str, uri: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    (status, json)
+}
+
+fn ids(json: &Value) -> Vec<&str> {
+    json.as_object()
+        .unwrap()
+        .keys()
+        .map(|id| id.as_str())
+        .collect()
+}
+
+#[tokio::test]
+async fn modules_are_filtered_by_chara_and_id() {
+    let (status, json) = send(app(), "GET", "/modules").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ids(&json), ["1", "2", "3"]);
+
+    let (_, json) = send(app(), "GET", "/modules?chara=MIK").await;
+    assert_eq!(ids(&json), ["1", "2"]);
+
+    let (_, json) = send(app(), "GET", "/modules?id_min=2&id_max=3").await;
+    assert_eq!(ids(&json), ["2", "3"]);
+
+    let (_, json) = send(app(), "GET", "/modules?chara=MIK&id_min=2&id_max=3").await;
+    assert_eq!(ids(&json), ["2"]);
+    assert_eq!(json["2"]["name"]["en"], "Miku fixture 2");
+}
+
+#[tokio::test]
+async fn cstm_items_are_filtered_by_part() {
+    let (status, json) = send(app(), "GET", "/cstm_items").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ids(&json), ["10", "11"]);
+
+    let (_, json) = send(app(), "GET", "/cstm_items?part=FACE").await;
+    assert_eq!(ids(&json), ["11"]);
+    assert_eq!(json["11"]["name"]["en"], "Glasses");
+}
+
+#[tokio::test]
+async fn single_entries_and_missing_ids() {
+    let (status, json) = send(app(), "GET", "/modules/3").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["chara"], "RIN");
+
+    let (status, json) = send(app(), "GET", "/cstm_items/10").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["part"], "KAMI");
+
+    let (status, _) = send(app(), "GET", "/modules/999").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(app(), "GET", "/cstm_items/999").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn reload_bumps_the_version() {
+    let app = app();
+    let (_, before) = send(app.clone(), "GET", "/version").await;
+
+    let (status, reloaded) = send(app.clone(), "POST", "/reload").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        reloaded.as_u64(),
+        before.as_u64().map(|version| version + 1)
+    );
+
+    let (_, after) = send(app.clone(), "GET", "/version").await;
+    assert_eq!(after, reloaded);
+    let (_, json) = send(app, "GET", "/modules").await;
+    assert_eq!(ids(&json), ["1", "2", "3"]);
+}
+
+#[tokio::test]
+async fn reload_without_mods_folder_is_not_found() {
+    let module_db = module_db::ModuleDb::from_mods_folder(MODS).unwrap();
+    let app = router(ServerState::new(module_db, None));
+    let (status, _) = send(app, "POST", "/reload").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn openapi_lists_the_routes() {
+    let (status, json) = send(app(), "GET", "/openapi.json").await;
+    assert_eq!(status, StatusCode::OK);
+    for path in ["/modules", "/modules/{id}", "/cstm_items", "/reload"] {
+        assert!(json["paths"].get(path).is_some(), "{path} missing");
+    }
+    assert!(json["components"]["schemas"].get("ModuleDb").is_some());
+}
@@ -0,0 +1,67 @@
+use module_db::vfs::{loader_priority, Vfs};
+use std::path::{Path, PathBuf};
+
+const GAME: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/vfs/game");
+
+fn game(path: &str) -> PathBuf {
+    Path::new(GAME).join(path)
+}
+
+fn names(vfs: &Vfs) -> Vec<&str> {
+    vfs.mods
+        .iter()
+        .map(|vfs_mod| vfs_mod.name.as_str())
+        .collect()
+}
+
+#[test]
+fn priority_list_then_names() {
+    // The disabled mod is left out entirely
+    let vfs = Vfs::from_mods_folder(game("mods"), &[]).unwrap();
+    assert_eq!(names(&vfs), ["alpha", "beta", "zeta"]);
+    assert_eq!(vfs.base, None);
+
+    assert_eq!(loader_priority(game("mods")), ["zeta"]);
+    let vfs = Vfs::from_game_folder(GAME).unwrap();
+    assert_eq!(names(&vfs), ["zeta", "alpha", "beta"]);
+    assert_eq!(vfs.base, Some(PathBuf::from(GAME)));
+
+    let priority = [String::from("beta"), String::from("missing")];
+    let vfs = Vfs::from_mods_folder(game("mods"), &priority).unwrap();
+    assert_eq!(names(&vfs), ["beta", "alpha", "zeta"]);
+}
+
+#[test]
+fn paths_ignore_case() {
+    let vfs = Vfs::from_game_folder(GAME).unwrap();
+
+    // alpha ships rom/Objset/MIKITM001.farc
+    let providers = vfs.providers("rom/objset/mikitm001.farc");
+    let mods = providers
+        .iter()
+        .map(|file| file.mod_name.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(mods, [Some("alpha"), Some("beta"), None]);
+    assert_eq!(
+        providers[0].path,
+        game("mods/alpha/rom/Objset/MIKITM001.farc")
+    );
+    assert_eq!(providers[2].path, game("rom/objset/mikitm001.farc"));
+
+    let file = vfs.resolve("\\ROM\\OBJSET\\MikItm002.farc").unwrap();
+    assert_eq!(file.mod_name.as_deref(), Some("zeta"));
+    assert_eq!(file.path, game("mods/zeta/rom/objset/mikitm002.farc"));
+
+    assert!(vfs.resolve("rom/objset/mikitm003.farc").is_none());
+}
+
+#[test]
+fn merged_tables_are_not_conflicts() {
+    let vfs = Vfs::from_game_folder(GAME).unwrap();
+
+    // alpha and beta both ship mod_gm_module_tbl.farc and lang2/mod_str_array.toml,
+    // which DML merges instead of picking one
+    let conflicts = vfs.conflicts();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts["rom/objset/mikitm001.farc"], ["alpha", "beta"]);
+}